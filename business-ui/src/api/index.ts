@@ -10,9 +10,56 @@ export interface LoyalityPass {
   totalPoints: number;
   currentPoints: number;
   passHolderName: string;
+  passHolderEmail?: string;
+  passHolderPhone?: string;
   lastUsedAt?: string;
 }
 
+export type ScanAction =
+  | 'addPoints'
+  | 'redeemBonus'
+  | 'redeemCoupon'
+  | 'topUpGiftCard'
+  | 'spendGiftCard'
+  | 'checkInTicket';
+
+export interface Coupon {
+  serialNumber: string;
+  discountText: string;
+  details?: string;
+  validFrom?: string;
+  validUntil?: string;
+  maxRedemptions: number;
+  redemptions: number;
+}
+
+export interface GiftCard {
+  serialNumber: string;
+  /** In minor currency units, e.g. cents. */
+  balance: number;
+  currency: string;
+  passHolderName?: string;
+}
+
+export interface EventTicket {
+  serialNumber: string;
+  eventId: string;
+  attendeeName?: string;
+  seat?: string;
+  checkedInAt?: string;
+}
+
+export interface Scan {
+  scanId: number;
+  scannedAt: string;
+  serialNumber: string;
+  memberCode: string;
+  kind: 'loyality' | 'coupon' | 'eventTicket' | 'giftCard';
+  signed: boolean;
+  pass: LoyalityPass | Coupon | GiftCard | EventTicket;
+  actions: ScanAction[];
+}
+
 const getAuthHeaders = (token: string) => ({
   headers: {
     Authorization: `Bearer ${token}`,
@@ -32,5 +79,37 @@ export const redeemBonus = (serialNumber: string, token: string) => {
 };
 
 export const createPass = (token: string) => {
-  return apiClient.get('/passes', { ...getAuthHeaders(token), responseType: 'blob' });
+  return apiClient.post('/passes', {}, { ...getAuthHeaders(token), responseType: 'blob' });
+};
+
+export const scanPass = (payload: string, token: string) => {
+  return apiClient.post<Scan>(
+    '/scans',
+    { payload, store: import.meta.env.VITE_STORE },
+    getAuthHeaders(token),
+  );
+};
+
+export const redeemCoupon = (serialNumber: string, token: string) => {
+  return apiClient.post<Coupon>(`/passes/${serialNumber}/coupon/redemption`, {}, getAuthHeaders(token));
+};
+
+export const topUpGiftCard = (serialNumber: string, amount: number, token: string) => {
+  return apiClient.post<GiftCard>(`/passes/${serialNumber}/gift-card/top-ups`, { amount }, getAuthHeaders(token));
+};
+
+export const spendGiftCard = (serialNumber: string, amount: number, token: string) => {
+  return apiClient.post<GiftCard>(`/passes/${serialNumber}/gift-card/spendings`, { amount }, getAuthHeaders(token));
+};
+
+export const checkInTicket = (serialNumber: string, token: string) => {
+  return apiClient.post<EventTicket>(`/passes/${serialNumber}/event-ticket/check-in`, {}, getAuthHeaders(token));
+};
+
+/** The message of an error response of the API, for the staff. */
+export const errorMessage = (err: unknown, fallback: string) => {
+  if (axios.isAxiosError(err) && err.response?.data?.clientMessage) {
+    return err.response.data.clientMessage as string;
+  }
+  return fallback;
 };
@@ -1,13 +1,21 @@
-import { useEffect, useRef } from 'react';
+import { useEffect, useRef, useState } from 'react';
 import { Html5QrcodeScanner } from 'html5-qrcode';
 import { useNavigate } from 'react-router-dom';
+import { useAuth } from '@clerk/clerk-react';
+import { errorMessage, scanPass, type Scan } from '../api';
+import ScanResult from './ScanResult';
 
 export default function QrScanner() {
   const scannerRef = useRef<HTMLDivElement>(null);
   const navigate = useNavigate();
+  const { getToken } = useAuth();
+  const [error, setError] = useState('');
+  const [scan, setScan] = useState<Scan | null>(null);
+  // Changed to start a new scanner, the old one is cleared after each scan.
+  const [scannerKey, setScannerKey] = useState(0);
 
   useEffect(() => {
-    if (!scannerRef.current) return;
+    if (scan || !scannerRef.current) return;
 
     const scanner = new Html5QrcodeScanner(
       scannerRef.current.id,
@@ -18,19 +26,48 @@ export default function QrScanner() {
       false
     );
 
-    const onScanSuccess = (decodedText: string) => {
-      // Assuming the decoded text is the serial number
-      // In a real scenario, you might need to parse a URL to get the serial number
+    const onScanSuccess = async (decodedText: string) => {
       scanner.clear();
-      navigate(`/pass/${decodedText}`);
+      try {
+        const token = await getToken();
+        if (!token) throw new Error("Not authenticated");
+        // The barcode can be a signed barcode, a member code or a serial number, the server
+        // resolves it to the pass.
+        const response = await scanPass(decodedText, token);
+        setError('');
+        if (response.data.kind === 'loyality' && response.data.signed) {
+          navigate(`/pass/${response.data.serialNumber}`);
+        } else {
+          setScan(response.data);
+        }
+      } catch (err) {
+        setError(errorMessage(err, 'The barcode does not belong to a valid pass.'));
+        console.error(err);
+        setScannerKey((key) => key + 1);
+      }
     };
 
     scanner.render(onScanSuccess, undefined);
 
     return () => {
-      scanner.clear();
+      // Already cleared after a scan.
+      scanner.clear().catch(() => {});
     };
-  }, [navigate]);
+  }, [navigate, getToken, scan, scannerKey]);
+
+  const scanNext = () => {
+    setScan(null);
+    setScannerKey((key) => key + 1);
+  };
 
-  return <div id="qr-reader" ref={scannerRef} className="w-full md:w-1/2 mx-auto"></div>;
+  return (
+    <>
+      {error && <div className="text-center text-red-500 mb-4">{error}</div>}
+      {scan ? (
+        <ScanResult scan={scan} onDone={scanNext} />
+      ) : (
+        <div id="qr-reader" key={scannerKey} ref={scannerRef} className="w-full md:w-1/2 mx-auto"></div>
+      )}
+    </>
+  );
 }
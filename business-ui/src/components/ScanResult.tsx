@@ -0,0 +1,163 @@
+import { useState } from 'react';
+import { Link } from 'react-router-dom';
+import { useAuth } from '@clerk/clerk-react';
+import {
+  checkInTicket,
+  errorMessage,
+  redeemCoupon,
+  spendGiftCard,
+  topUpGiftCard,
+  type Coupon,
+  type EventTicket,
+  type GiftCard,
+  type Scan,
+  type ScanAction,
+} from '../api';
+
+interface ScanResultProps {
+  scan: Scan;
+  onDone: () => void;
+}
+
+const formatMoney = (amount: number, currency: string) =>
+  new Intl.NumberFormat(undefined, { style: 'currency', currency }).format(amount / 100);
+
+/** A scanned pass with the actions the server allows for it. */
+export default function ScanResult({ scan, onDone }: ScanResultProps) {
+  const { getToken } = useAuth();
+  const [amount, setAmount] = useState('');
+  const [message, setMessage] = useState('');
+  const [error, setError] = useState('');
+
+  const run = async (action: ScanAction) => {
+    try {
+      const token = await getToken();
+      if (!token) throw new Error("Not authenticated");
+      const cents = Math.round(parseFloat(amount.replace(',', '.')) * 100);
+      switch (action) {
+        case 'redeemCoupon':
+          await redeemCoupon(scan.serialNumber, token);
+          setMessage('Coupon redeemed.');
+          break;
+        case 'topUpGiftCard': {
+          const response = await topUpGiftCard(scan.serialNumber, cents, token);
+          setMessage(`New balance: ${formatMoney(response.data.balance, response.data.currency)}`);
+          break;
+        }
+        case 'spendGiftCard': {
+          const response = await spendGiftCard(scan.serialNumber, cents, token);
+          setMessage(`New balance: ${formatMoney(response.data.balance, response.data.currency)}`);
+          break;
+        }
+        case 'checkInTicket':
+          await checkInTicket(scan.serialNumber, token);
+          setMessage('Ticket checked in.');
+          break;
+        default:
+          return;
+      }
+      setError('');
+    } catch (err) {
+      setError(errorMessage(err, 'The action failed.'));
+      console.error(err);
+    }
+  };
+
+  const labels: Record<ScanAction, string> = {
+    addPoints: 'Add Points',
+    redeemBonus: 'Redeem Bonus',
+    redeemCoupon: 'Redeem Coupon',
+    topUpGiftCard: 'Top Up',
+    spendGiftCard: 'Spend',
+    checkInTicket: 'Check In',
+  };
+
+  const details = () => {
+    switch (scan.kind) {
+      case 'coupon': {
+        const coupon = scan.pass as Coupon;
+        return (
+          <>
+            <p><strong>Coupon:</strong> {coupon.discountText}</p>
+            <p><strong>Redemptions:</strong> {coupon.redemptions} of {coupon.maxRedemptions}</p>
+          </>
+        );
+      }
+      case 'giftCard': {
+        const giftCard = scan.pass as GiftCard;
+        return <p><strong>Balance:</strong> {formatMoney(giftCard.balance, giftCard.currency)}</p>;
+      }
+      case 'eventTicket': {
+        const ticket = scan.pass as EventTicket;
+        return (
+          <>
+            <p><strong>Guest:</strong> {ticket.attendeeName ?? 'N/A'}</p>
+            <p><strong>Seat:</strong> {ticket.seat ?? 'N/A'}</p>
+            <p><strong>Checked In:</strong> {ticket.checkedInAt ? new Date(ticket.checkedInAt).toLocaleString() : 'No'}</p>
+          </>
+        );
+      }
+      default:
+        return null;
+    }
+  };
+
+  const giftCardActions = scan.actions.filter((a) => a === 'topUpGiftCard' || a === 'spendGiftCard');
+  const otherActions = scan.actions.filter((a) => !giftCardActions.includes(a));
+
+  return (
+    <div className="max-w-md mx-auto bg-white rounded-lg shadow-md p-6">
+      {!scan.signed && (
+        <div className="mb-4 p-2 rounded bg-yellow-100 text-yellow-800">
+          The barcode is not signed, it could be copied from another pass. Check the pass in Wallet.
+        </div>
+      )}
+      <div className="space-y-2">
+        <p><strong>Member Code:</strong> {scan.memberCode}</p>
+        {details()}
+      </div>
+
+      {message && <div className="mt-4 text-green-600">{message}</div>}
+      {error && <div className="mt-4 text-red-500">{error}</div>}
+
+      {giftCardActions.length > 0 && (
+        <div className="mt-6">
+          <input
+            type="number"
+            value={amount}
+            onChange={(e) => setAmount(e.target.value)}
+            className="w-full p-2 border rounded mb-2"
+            min="0.01"
+            step="0.01"
+            placeholder="Amount"
+          />
+          <div className="flex gap-2">
+            {giftCardActions.map((action) => (
+              <button key={action} onClick={() => run(action)} className="flex-1 p-2 text-white bg-blue-500 rounded hover:bg-blue-600">
+                {labels[action]}
+              </button>
+            ))}
+          </div>
+        </div>
+      )}
+
+      {scan.kind === 'loyality' ? (
+        <Link to={`/pass/${scan.serialNumber}`} className="block mt-6 w-full p-2 text-center text-white bg-blue-500 rounded hover:bg-blue-600">
+          Open Pass
+        </Link>
+      ) : (
+        otherActions.map((action) => (
+          <button key={action} onClick={() => run(action)} className="mt-6 w-full p-2 text-white bg-green-500 rounded hover:bg-green-600">
+            {labels[action]}
+          </button>
+        ))
+      )}
+
+      {scan.actions.length === 0 && <p className="mt-6 text-gray-600">Nothing can be done with this pass.</p>}
+
+      <button onClick={onDone} className="mt-6 w-full p-2 border rounded hover:bg-gray-100">
+        Scan Next
+      </button>
+    </div>
+  );
+}
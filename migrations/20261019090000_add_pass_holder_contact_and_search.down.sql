@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS pass_type_loyality_pass_holder_phone_trgm_idx;
+DROP INDEX IF EXISTS pass_type_loyality_pass_holder_email_trgm_idx;
+DROP INDEX IF EXISTS pass_type_loyality_pass_holder_name_trgm_idx;
+
+ALTER TABLE pass_type_loyality
+    DROP COLUMN IF EXISTS pass_holder_phone,
+    DROP COLUMN IF EXISTS pass_holder_email;
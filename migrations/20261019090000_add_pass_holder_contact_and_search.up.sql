@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE pass_type_loyality
+    ADD COLUMN pass_holder_email VARCHAR(255),
+    ADD COLUMN pass_holder_phone VARCHAR(255);
+
+CREATE INDEX pass_type_loyality_pass_holder_name_trgm_idx
+    ON pass_type_loyality USING GIN (pass_holder_name gin_trgm_ops);
+CREATE INDEX pass_type_loyality_pass_holder_email_trgm_idx
+    ON pass_type_loyality USING GIN (pass_holder_email gin_trgm_ops);
+CREATE INDEX pass_type_loyality_pass_holder_phone_trgm_idx
+    ON pass_type_loyality USING GIN (pass_holder_phone gin_trgm_ops);
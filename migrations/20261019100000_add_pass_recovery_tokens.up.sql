@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+CREATE TABLE pass_recovery_tokens (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    pass_serial_number VARCHAR(255) NOT NULL REFERENCES passes(serial_number) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_by VARCHAR(255) NOT NULL
+);
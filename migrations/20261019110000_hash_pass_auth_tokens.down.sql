@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+-- Only the hashes of the tokens are stored, the plain tokens the installed passes carry can't be
+-- restored. Random tokens would lock out every installed pass, so this migration can't be undone.
+DO $$
+BEGIN
+    RAISE EXCEPTION 'hash_pass_auth_tokens can not be reverted, the plain auth tokens are gone';
+END
+$$;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+CREATE TABLE pass_auth_tokens (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    pass_serial_number VARCHAR(255) NOT NULL REFERENCES passes(serial_number) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    needs_rotation BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX pass_auth_tokens_pass_serial_number_idx ON pass_auth_tokens (pass_serial_number);
+
+-- The old tokens are predictable, so they only stay valid until the pass gets fetched the next time.
+INSERT INTO pass_auth_tokens (token_hash, pass_serial_number, created_at, needs_rotation)
+SELECT encode(sha256(convert_to(auth_token, 'UTF8')), 'hex'), serial_number, created_at, TRUE
+FROM passes;
+
+ALTER TABLE passes DROP COLUMN auth_token;
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE pass_auth_tokens DROP COLUMN IF EXISTS expires_at;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- Rotated tokens stay valid until they expire, so all devices of the pass can fetch the new one.
+ALTER TABLE pass_auth_tokens ADD COLUMN expires_at TIMESTAMP;
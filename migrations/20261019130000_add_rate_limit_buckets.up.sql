@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+CREATE TABLE rate_limit_buckets (
+    bucket VARCHAR(512) PRIMARY KEY,
+    window_start TIMESTAMP NOT NULL,
+    hits INTEGER NOT NULL
+);
+
+CREATE INDEX rate_limit_buckets_window_start_idx ON rate_limit_buckets (window_start);
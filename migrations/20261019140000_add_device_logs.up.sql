@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+CREATE TABLE device_logs (
+    message_hash VARCHAR(64) PRIMARY KEY,
+    message TEXT NOT NULL,
+    device_library_id VARCHAR(255),
+    pass_type_id VARCHAR(255),
+    pass_serial_number VARCHAR(255),
+    first_logged_at TIMESTAMP,
+    last_logged_at TIMESTAMP,
+    first_seen_at TIMESTAMP NOT NULL,
+    last_seen_at TIMESTAMP NOT NULL,
+    occurrences INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX device_logs_last_seen_at_idx ON device_logs (last_seen_at);
+CREATE INDEX device_logs_device_library_id_idx ON device_logs (device_library_id);
+CREATE INDEX device_logs_pass_serial_number_idx ON device_logs (pass_serial_number);
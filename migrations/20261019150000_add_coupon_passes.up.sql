@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+ALTER TYPE pass_type ADD VALUE IF NOT EXISTS 'COUPON';
+
+CREATE TABLE pass_type_coupon (
+    serial_number VARCHAR(255) PRIMARY KEY REFERENCES passes(serial_number) ON DELETE CASCADE,
+    discount_text VARCHAR(255) NOT NULL,
+    details TEXT,
+    valid_from TIMESTAMP,
+    valid_until TIMESTAMP,
+    max_redemptions INTEGER NOT NULL CHECK (max_redemptions > 0),
+    redemptions INTEGER NOT NULL DEFAULT 0 CHECK (redemptions >= 0 AND redemptions <= max_redemptions),
+    last_redeemed_at TIMESTAMP
+);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS gift_card_transactions;
+DROP TABLE IF EXISTS pass_type_gift_card;
+
+DELETE FROM passes WHERE type = 'GIFT_CARD';
+
+-- Values can't be removed from an enum, so the type is recreated without it.
+ALTER TYPE pass_type RENAME TO pass_type_old;
+CREATE TYPE pass_type AS ENUM ('LOYALITY', 'COUPON');
+ALTER TABLE passes ALTER COLUMN type TYPE pass_type USING type::text::pass_type;
+DROP TYPE pass_type_old;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+ALTER TYPE pass_type ADD VALUE IF NOT EXISTS 'GIFT_CARD';
+
+CREATE TABLE pass_type_gift_card (
+    serial_number VARCHAR(255) PRIMARY KEY REFERENCES passes(serial_number) ON DELETE CASCADE,
+    balance BIGINT NOT NULL CHECK (balance >= 0),
+    currency VARCHAR(3) NOT NULL,
+    pass_holder_name VARCHAR(255),
+    last_used_at TIMESTAMP
+);
+
+CREATE TABLE gift_card_transactions (
+    id BIGSERIAL PRIMARY KEY,
+    pass_serial_number VARCHAR(255) NOT NULL REFERENCES pass_type_gift_card(serial_number) ON DELETE CASCADE,
+    amount BIGINT NOT NULL,
+    balance_after BIGINT NOT NULL,
+    note TEXT,
+    created_at TIMESTAMP NOT NULL,
+    created_by VARCHAR(255)
+);
+
+CREATE INDEX gift_card_transactions_pass_serial_number_idx ON gift_card_transactions (pass_serial_number, created_at);
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+ALTER TYPE pass_type ADD VALUE IF NOT EXISTS 'EVENT_TICKET';
+
+CREATE TABLE events (
+    id VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    venue VARCHAR(255),
+    starts_at TIMESTAMP NOT NULL,
+    ends_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL,
+    created_by VARCHAR(255) NOT NULL
+);
+
+CREATE TABLE pass_type_event_ticket (
+    serial_number VARCHAR(255) PRIMARY KEY REFERENCES passes(serial_number) ON DELETE CASCADE,
+    event_id VARCHAR(255) NOT NULL REFERENCES events(id),
+    attendee_name VARCHAR(255),
+    seat VARCHAR(255),
+    checked_in_at TIMESTAMP
+);
+
+CREATE INDEX pass_type_event_ticket_event_id_idx ON pass_type_event_ticket (event_id);
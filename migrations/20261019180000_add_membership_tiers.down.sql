@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE pass_type_loyality DROP COLUMN IF EXISTS tier_reached_at;
+ALTER TABLE pass_type_loyality DROP COLUMN IF EXISTS tier;
+ALTER TABLE pass_type_loyality DROP COLUMN IF EXISTS lifetime_points;
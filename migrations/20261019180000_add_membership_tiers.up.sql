@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Points are reset when a bonus is redeemed, so the collected points are counted separately.
+ALTER TABLE pass_type_loyality ADD COLUMN lifetime_points INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pass_type_loyality ADD COLUMN tier VARCHAR(64);
+ALTER TABLE pass_type_loyality ADD COLUMN tier_reached_at TIMESTAMP;
+
+UPDATE pass_type_loyality SET lifetime_points = current_points + already_redeemed * total_points;
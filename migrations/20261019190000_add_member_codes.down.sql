@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE passes DROP COLUMN IF EXISTS member_code;
@@ -0,0 +1,36 @@
+-- Add up migration script here
+
+-- Same format as `member_code::generate`: seven Crockford base32 symbols and a mod 37 check
+-- symbol.
+CREATE FUNCTION pg_temp.new_member_code() RETURNS VARCHAR AS $$
+DECLARE
+    alphabet CONSTANT TEXT := '0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U';
+    value CONSTANT BIGINT := floor(random() * 34359738368)::BIGINT;
+    remaining BIGINT := value;
+    code TEXT := '';
+BEGIN
+    FOR i IN 1..7 LOOP
+        code := substr(alphabet, (remaining % 32)::INT + 1, 1) || code;
+        remaining := remaining / 32;
+    END LOOP;
+
+    RETURN code || substr(alphabet, (value % 37)::INT + 1, 1);
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE passes ADD COLUMN member_code VARCHAR(16);
+
+UPDATE passes SET member_code = pg_temp.new_member_code();
+
+-- Draw new codes for the unlikely duplicates before the constraint is added.
+DO $$
+BEGIN
+    WHILE EXISTS (SELECT 1 FROM passes GROUP BY member_code HAVING COUNT(*) > 1) LOOP
+        UPDATE passes SET member_code = pg_temp.new_member_code()
+        WHERE member_code IN (SELECT member_code FROM passes GROUP BY member_code HAVING COUNT(*) > 1);
+    END LOOP;
+END
+$$;
+
+ALTER TABLE passes ALTER COLUMN member_code SET NOT NULL;
+ALTER TABLE passes ADD CONSTRAINT passes_member_code_key UNIQUE (member_code);
@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE scan_events (
+    id BIGSERIAL PRIMARY KEY,
+    pass_serial_number VARCHAR(255) NOT NULL REFERENCES passes(serial_number) ON DELETE CASCADE,
+    scanned_at TIMESTAMP NOT NULL,
+    scanned_by VARCHAR(255) NOT NULL,
+    store VARCHAR(255),
+    signed BOOLEAN NOT NULL
+);
+
+CREATE INDEX scan_events_pass_serial_number_scanned_at_idx ON scan_events (pass_serial_number, scanned_at);
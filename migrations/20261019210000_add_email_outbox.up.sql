@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE email_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    pass_serial_number VARCHAR(255) REFERENCES passes(serial_number) ON DELETE CASCADE,
+    recipient VARCHAR(255) NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL,
+    sent_at TIMESTAMP,
+    failed_at TIMESTAMP
+);
+
+CREATE INDEX email_outbox_pending_idx ON email_outbox (next_attempt_at) WHERE sent_at IS NULL AND failed_at IS NULL;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- The replacement of a rotated token is derived from the token with this key, so every fetch
+-- with the rotated token gets the same replacement instead of adding another token.
+ALTER TABLE pass_auth_tokens ADD COLUMN replacement_key VARCHAR(64);
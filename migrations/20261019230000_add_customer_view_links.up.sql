@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- The tokens of the customer view links are derived from the serial number and the creation time
+-- with the configured secret, so the current link can be printed on the pass again.
+CREATE TABLE customer_view_links (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    pass_serial_number VARCHAR(255) NOT NULL REFERENCES passes(serial_number) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX customer_view_links_pass_serial_number_idx ON customer_view_links (pass_serial_number);
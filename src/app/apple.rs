@@ -3,7 +3,7 @@ use tracing::info;
 
 use crate::{
     db::{queries::pass_registered_for_device, DbDevice, DbPass},
-    Result,
+    Error, Result,
 };
 
 use super::App;
@@ -77,11 +77,8 @@ DO NOTHING
             info!(device_library_id = device_library_id, "device deleted");
         }
 
-        if DbPass::count_of_devices(serial_number, &self.db_pool).await? == 0 {
-            DbPass::delete(serial_number, &self.db_pool).await?;
-            info!(serial_number = serial_number, "pass deleted");
-        }
-
+        // The pass is kept when it was removed from the last device. It can be moved to a new
+        // phone with a recovery link, which may only be opened after the old phone removed it.
         info!(devie_library_id = device_library_id, "device unregistered");
 
         Ok(())
@@ -93,6 +90,10 @@ DO NOTHING
         device_library_id: &str,
         passes_updated_since: Option<DateTime<Utc>>,
     ) -> Result<(Vec<String>, DateTime<Utc>)> {
+        if !self.pass_types().contains(pass_type_id) {
+            return Err(Error::PassNotFound);
+        }
+
         let passes = DbPass::from_pass_type_last_updated_device_library_id(
             pass_type_id,
             passes_updated_since.map(|d| d.naive_utc()),
@@ -0,0 +1,235 @@
+use crate::{db::DbPass, member_code, token, wallet::PassKind, Error, Result};
+
+use super::{pass::pass_kind, App};
+
+/// Length of the signature in a barcode, in bytes of the HMAC-SHA256.
+const SIGNATURE_LENGTH: usize = 16;
+
+/// Signs the barcodes, so a pass can only be scanned with its current barcode. The barcode is
+/// `{member_code}.{version}.{signature}`, where the version is the last update of the pass, so
+/// each update of the pass invalidates screenshots of the old barcode.
+#[derive(Debug)]
+pub struct BarcodeSigner {
+    secret: Vec<u8>,
+}
+
+impl BarcodeSigner {
+    pub fn new(secret: &str) -> Result<Self> {
+        if secret.len() < 32 {
+            return Err(Error::Other(
+                "the barcode secret must have at least 32 characters".into(),
+            ));
+        }
+
+        Ok(Self {
+            secret: secret.as_bytes().to_vec(),
+        })
+    }
+
+    fn signature(&self, serial_number: &str, version: i64) -> Result<String> {
+        token::hmac(
+            &self.secret,
+            &format!("{serial_number}.{version}"),
+            SIGNATURE_LENGTH,
+        )
+    }
+
+    /// The barcode message of the pass in its current version.
+    pub fn sign(&self, pass: &DbPass) -> Result<String> {
+        let version = pass.last_updated_at.and_utc().timestamp_micros();
+
+        Ok(format!(
+            "{}.{version}.{}",
+            pass.member_code,
+            self.signature(&pass.serial_number, version)?
+        ))
+    }
+
+    /// Whether the signature of the barcode is valid for the pass with `serial_number`.
+    fn verify(&self, serial_number: &str, barcode: &SignedBarcode) -> Result<bool> {
+        let signature = self.signature(serial_number, barcode.version)?;
+
+        Ok(token::signature_eq(barcode.signature, &signature))
+    }
+}
+
+/// A signed barcode, split into its parts.
+struct SignedBarcode<'a> {
+    member_code: &'a str,
+    version: i64,
+    signature: &'a str,
+}
+
+impl<'a> SignedBarcode<'a> {
+    fn parse(payload: &'a str) -> Option<Self> {
+        let mut parts = payload.split('.');
+        let (Some(member_code), Some(version), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+
+        Some(Self {
+            member_code,
+            version: version.parse().ok()?,
+            signature,
+        })
+    }
+}
+
+/// The pass a scanned barcode belongs to.
+pub struct ResolvedBarcode {
+    pub pass: DbPass,
+    pub kind: PassKind,
+    /// Whether the barcode carried a valid signature, unsigned barcodes are the serial number or
+    /// member code, which can be copied from any pass. Only accepted without a barcode secret.
+    pub signed: bool,
+}
+
+impl App {
+    /// Finds the pass of a scanned barcode, which is either a signed barcode, a member code or a
+    /// serial number. Signed barcodes of an older version of the pass are rejected, and once
+    /// barcodes are signed, so are member codes and serial numbers.
+    pub async fn resolve_barcode(&self, payload: &str) -> Result<ResolvedBarcode> {
+        let payload = payload.trim();
+
+        let Some(signed_barcode) = SignedBarcode::parse(payload) else {
+            if self.barcode_signer.is_some() {
+                return Err(Error::BarcodeUnsigned);
+            }
+
+            let serial_number = self.resolve_pass_reference(payload).await?;
+            let pass = DbPass::from_serial_number_optional(&serial_number, &self.db_pool)
+                .await?
+                .ok_or(Error::PassNotFound)?;
+
+            return Ok(ResolvedBarcode {
+                kind: pass_kind(&pass.r#type),
+                pass,
+                signed: false,
+            });
+        };
+
+        let barcode_signer = self.barcode_signer.as_ref().ok_or(Error::BarcodeInvalid)?;
+
+        let member_code =
+            member_code::normalize(signed_barcode.member_code).ok_or(Error::BarcodeInvalid)?;
+        let serial_number = DbPass::serial_number_from_member_code(&member_code, &self.db_pool)
+            .await?
+            .ok_or(Error::BarcodeInvalid)?;
+
+        if !barcode_signer.verify(&serial_number, &signed_barcode)? {
+            return Err(Error::BarcodeInvalid);
+        }
+
+        let pass = DbPass::from_serial_number_optional(&serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)?;
+
+        if pass.last_updated_at.and_utc().timestamp_micros() != signed_barcode.version {
+            return Err(Error::BarcodeOutdated);
+        }
+
+        Ok(ResolvedBarcode {
+            kind: pass_kind(&pass.r#type),
+            pass,
+            signed: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{BarcodeSigner, SignedBarcode};
+    use crate::db::{DbPass, DbPassTypeHelper};
+
+    fn signer() -> BarcodeSigner {
+        BarcodeSigner::new("0123456789abcdef0123456789abcdef").unwrap()
+    }
+
+    fn pass() -> DbPass {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_micro_opt(12, 0, 0, 123_456)
+            .unwrap();
+
+        DbPass {
+            serial_number: "0192a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b".into(),
+            pass_type_id: "pass.com.example".into(),
+            last_updated_at: now,
+            created_at: now,
+            r#type: DbPassTypeHelper::Loyality,
+            member_code: "4K7M2QXA".into(),
+        }
+    }
+
+    #[test]
+    fn signed_barcodes_round_trip() {
+        let signer = signer();
+        let pass = pass();
+
+        let payload = signer.sign(&pass).unwrap();
+        let barcode = SignedBarcode::parse(&payload).unwrap();
+
+        assert_eq!(barcode.member_code, pass.member_code);
+        assert_eq!(
+            barcode.version,
+            pass.last_updated_at.and_utc().timestamp_micros()
+        );
+        assert!(signer.verify(&pass.serial_number, &barcode).unwrap());
+    }
+
+    #[test]
+    fn rejects_tampered_signatures() {
+        let signer = signer();
+        let pass = pass();
+
+        let payload = signer.sign(&pass).unwrap();
+        let barcode = SignedBarcode::parse(&payload).unwrap();
+
+        let mut tampered = barcode.signature.to_string().into_bytes();
+        tampered[0] = if tampered[0] == b'0' { b'1' } else { b'0' };
+        let tampered = String::from_utf8(tampered).unwrap();
+        let tampered_barcode = SignedBarcode {
+            signature: &tampered,
+            ..barcode
+        };
+        assert!(!signer
+            .verify(&pass.serial_number, &tampered_barcode)
+            .unwrap());
+
+        // The signature of another version, pass or secret doesn't match either.
+        let barcode = SignedBarcode::parse(&payload).unwrap();
+        let other_version = SignedBarcode {
+            version: barcode.version + 1,
+            ..barcode
+        };
+        assert!(!signer.verify(&pass.serial_number, &other_version).unwrap());
+
+        let barcode = SignedBarcode::parse(&payload).unwrap();
+        assert!(!signer.verify("another-serial-number", &barcode).unwrap());
+
+        let other_signer = BarcodeSigner::new("fedcba9876543210fedcba9876543210").unwrap();
+        assert!(!other_signer.verify(&pass.serial_number, &barcode).unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_barcodes() {
+        // Wrong part counts.
+        assert!(SignedBarcode::parse("4K7M2QXA").is_none());
+        assert!(SignedBarcode::parse("4K7M2QXA.1760875200123456").is_none());
+        assert!(SignedBarcode::parse("4K7M2QXA.1760875200123456.abcdef.extra").is_none());
+        // Non-numeric versions.
+        assert!(SignedBarcode::parse("4K7M2QXA.v1.abcdef").is_none());
+        assert!(SignedBarcode::parse("4K7M2QXA..abcdef").is_none());
+        // Serial numbers contain no dots and are not taken for signed barcodes.
+        assert!(SignedBarcode::parse("0192a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b").is_none());
+    }
+
+    #[test]
+    fn rejects_short_secrets() {
+        assert!(BarcodeSigner::new("too short").is_err());
+    }
+}
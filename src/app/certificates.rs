@@ -0,0 +1,94 @@
+use std::{sync::Arc, time::Duration as StdDuration};
+
+use chrono::{Duration, Utc};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{error, info, warn};
+
+use crate::Result;
+
+use super::App;
+
+/// How often the certificate files are checked for changes.
+const CERTIFICATE_WATCH_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// How often upcoming certificate expiries are logged.
+const CERTIFICATE_EXPIRY_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(24 * 60 * 60);
+
+impl App {
+    /// Reads all certificates again and replaces the pass types with them. The old certificates
+    /// stay in use if any of the new ones can't be loaded.
+    pub fn reload_pass_types(&self) -> Result<()> {
+        let pass_types = self.pass_types().reload()?;
+        *self.pass_types.write().unwrap() = Arc::new(pass_types);
+
+        info!("reloaded pass type certificates");
+
+        Ok(())
+    }
+
+    /// Logs a warning for each certificate that expires within `warn_before`.
+    pub fn warn_expiring_certificates(&self, warn_before: Duration) {
+        let now = Utc::now();
+
+        for pass_type in self.pass_types().iter() {
+            for certificate in &pass_type.certificates {
+                if certificate.not_after <= now {
+                    error!(
+                        pass_type_id = pass_type.pass_type_identifier,
+                        certificate = certificate.name,
+                        path = certificate.path,
+                        not_after = %certificate.not_after,
+                        "certificate expired"
+                    );
+                } else if certificate.not_after - now <= warn_before {
+                    warn!(
+                        pass_type_id = pass_type.pass_type_identifier,
+                        certificate = certificate.name,
+                        path = certificate.path,
+                        not_after = %certificate.not_after,
+                        days_left = (certificate.not_after - now).num_days(),
+                        "certificate expires soon"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reloads the certificates on SIGHUP or when one of their files changes and periodically
+    /// warns about certificates that expire within `warn_before`, starting right away. Never
+    /// returns.
+    pub async fn run_certificate_maintenance(&self, warn_before: Duration) -> Result<()> {
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut watch_interval = tokio::time::interval(CERTIFICATE_WATCH_INTERVAL);
+        let mut expiry_interval = tokio::time::interval(CERTIFICATE_EXPIRY_CHECK_INTERVAL);
+        let mut modification_times = self.pass_types().file_modification_times();
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => {
+                    info!("received SIGHUP, reloading certificates");
+                }
+                _ = watch_interval.tick() => {
+                    if self.pass_types().file_modification_times() == modification_times {
+                        continue;
+                    }
+                    info!("certificate files changed, reloading certificates");
+                }
+                _ = expiry_interval.tick() => {
+                    self.warn_expiring_certificates(warn_before);
+                    continue;
+                }
+            }
+
+            // Keep the old modification times on failure, so the reload is retried with the
+            // next check, e.g. if only the certificate but not yet its key was replaced.
+            match self.reload_pass_types() {
+                Ok(()) => {
+                    modification_times = self.pass_types().file_modification_times();
+                    self.warn_expiring_certificates(warn_before);
+                }
+                Err(err) => error!("reloading certificates failed: {}", err),
+            }
+        }
+    }
+}
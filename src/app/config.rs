@@ -1,3 +1,14 @@
+use std::{cell::RefCell, collections::HashMap, env, fs, path::Path};
+
+use crate::{
+    email::{parse_mailbox, MailTransport, Mailer, SmtpSettings, SmtpTls},
+    http::{deserialize_optional_rate_limit, RateLimitRate, RateLimitRules},
+    wallet::{BarcodeKind, PassKind},
+    Error, Result,
+};
+
+use super::{BarcodeSigner, CustomerViewLinks, EmailDelivery, EmailTemplates};
+
 fn default_http_listener_host() -> String {
     "127.0.0.1:3000".into()
 }
@@ -6,6 +17,193 @@ fn default_http_disable_auth() -> bool {
     false
 }
 
+fn default_db_repair_orphaned_passes() -> bool {
+    false
+}
+
+fn default_pass_auth_token_grace_period_hours() -> u32 {
+    24 * 7
+}
+
+fn default_certificate_expiry_warning_days() -> u32 {
+    30
+}
+
+fn default_rate_limit_create_pass_per_ip() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 20,
+        window_secs: 60 * 60,
+    })
+}
+
+fn default_rate_limit_apple_log_per_ip() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 60,
+        window_secs: 60,
+    })
+}
+
+fn default_rate_limit_apple_registration_per_ip() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 120,
+        window_secs: 60,
+    })
+}
+
+fn default_rate_limit_apple_registration_per_device() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 30,
+        window_secs: 60,
+    })
+}
+
+fn default_rate_limit_apple_registration_per_serial_number() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 30,
+        window_secs: 60,
+    })
+}
+
+fn default_rate_limit_customer_pass_download_per_ip() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 30,
+        window_secs: 60 * 60,
+    })
+}
+
+fn default_rate_limit_customer_pass_download_per_serial_number() -> Option<RateLimitRate> {
+    Some(RateLimitRate {
+        limit: 10,
+        window_secs: 60 * 60,
+    })
+}
+
+#[derive(serde::Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreKind {
+    #[default]
+    Memory,
+    Postgres,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTransportKind {
+    Smtp,
+    /// Writes the emails as `.eml` files into `email_file_dir`.
+    File,
+}
+
+/// Subject and body of the pass emails for one store and locale.
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct EmailTemplateConfig {
+    /// Used for all stores if not set.
+    pub store: Option<String>,
+    /// E.g. `de` or `en-GB`.
+    pub locale: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Certificates of one pass type identifier.
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(try_from = "PassTypeConfigFile")]
+pub struct PassTypeConfig {
+    pub kind: PassKind,
+    pub pass_type_id: String,
+    pub signing_cert_path: String,
+    pub signing_key_path: String,
+    pub signing_key_token: String,
+    /// The WWDR intermediate certificate matching the signing certificate, defaults to G4.
+    pub wwdr_cert_path: Option<String>,
+    pub apn_signing_cert_p12_path: String,
+    pub apn_signing_cert_p12_token: String,
+    /// Format of the barcodes: `qr`, `pdf417`, `aztec` or `code128`. It must be readable by the
+    /// scanners of every store the passes of this type are shown in. The loyality pass type
+    /// configured by the settings uses `pass_barcode_format`.
+    #[serde(default)]
+    pub barcode_format: BarcodeKind,
+}
+
+/// A pass type as written in the pass types file, where the secrets can also be read from the
+/// file named by their `*_file` key.
+#[derive(serde::Deserialize)]
+struct PassTypeConfigFile {
+    kind: PassKind,
+    pass_type_id: String,
+    signing_cert_path: String,
+    signing_key_path: String,
+    signing_key_token: Option<String>,
+    signing_key_token_file: Option<String>,
+    wwdr_cert_path: Option<String>,
+    apn_signing_cert_p12_path: String,
+    apn_signing_cert_p12_token: Option<String>,
+    apn_signing_cert_p12_token_file: Option<String>,
+    #[serde(default)]
+    barcode_format: BarcodeKind,
+}
+
+impl TryFrom<PassTypeConfigFile> for PassTypeConfig {
+    type Error = String;
+
+    fn try_from(file: PassTypeConfigFile) -> std::result::Result<Self, Self::Error> {
+        Ok(Self {
+            signing_key_token: secret_value(
+                "signing_key_token",
+                file.signing_key_token,
+                file.signing_key_token_file,
+            )?,
+            apn_signing_cert_p12_token: secret_value(
+                "apn_signing_cert_p12_token",
+                file.apn_signing_cert_p12_token,
+                file.apn_signing_cert_p12_token_file,
+            )?,
+            kind: file.kind,
+            pass_type_id: file.pass_type_id,
+            signing_cert_path: file.signing_cert_path,
+            signing_key_path: file.signing_key_path,
+            wwdr_cert_path: file.wwdr_cert_path,
+            apn_signing_cert_p12_path: file.apn_signing_cert_p12_path,
+            barcode_format: file.barcode_format,
+        })
+    }
+}
+
+/// Takes a secret from its value or reads it from the file at `path`.
+fn secret_value(
+    name: &str,
+    value: Option<String>,
+    path: Option<String>,
+) -> std::result::Result<String, String> {
+    match (value, path) {
+        (Some(_), Some(_)) => Err(format!(
+            "{name} and {name}_file are both set, only set one of them"
+        )),
+        (Some(value), None) => Ok(value),
+        (None, Some(path)) => fs::read_to_string(&path)
+            .map(|value| value.trim_end().to_string())
+            .map_err(|e| format!("{name}_file: can't read {path}: {e}")),
+        (None, None) => Err(format!("{name} or {name}_file is required")),
+    }
+}
+
+/// A membership tier of the loyality pass. Passes reach a tier once either threshold is met.
+/// Loyality passes don't record what the customer spent, so there are no spend thresholds.
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MembershipTierConfig {
+    /// Stored on the passes, must not change once passes reached the tier.
+    pub id: String,
+    /// Shown on the pass, e.g. `Gold`.
+    pub label: String,
+    pub min_redemptions: Option<i32>,
+    pub min_lifetime_points: Option<i32>,
+    /// RGB colors of the pass in this tier.
+    pub background_color: [u8; 3],
+    pub foreground_color: Option<[u8; 3]>,
+    pub label_color: Option<[u8; 3]>,
+}
+
 #[derive(serde::Deserialize, Debug)]
 pub struct AppConfig {
     #[serde(default = "default_http_listener_host")]
@@ -14,9 +212,21 @@ pub struct AppConfig {
     pub pass_signing_cert_path: String,
     pub pass_signing_key_path: String,
     pub pass_signing_key_token: String,
+    pub pass_wwdr_cert_path: Option<String>,
     pub pass_team_identifier: String,
     pub pass_type_id: String,
     pub pass_web_service_url: String,
+    /// If set, barcodes carry a signature and change with each update of the pass, so
+    /// screenshots of old barcodes can't be scanned. At least 32 characters.
+    pub pass_barcode_secret: Option<String>,
+    /// Barcode format of the loyality pass type configured by these settings, which new loyality
+    /// passes are issued with: `qr`, `pdf417`, `aztec` or `code128`. Set it to a format the
+    /// scanners of all stores can read, e.g. `pdf417` if one store only reads PDF417 or Code128.
+    #[serde(default)]
+    pub pass_barcode_format: BarcodeKind,
+    /// If set together with `http_public_url`, the passes link to a page where the customer sees
+    /// the pass and its history. At least 32 characters.
+    pub pass_customer_view_secret: Option<String>,
     pub pass_logo_path: String,
     pub pass_icon_path: String,
     pub apn_signing_cert_p12_path: String,
@@ -24,13 +234,473 @@ pub struct AppConfig {
     pub background_image_path: String,
     pub point_image_path: String,
     pub bonus_point_image_path: String,
+    /// JSON file with a list of further pass types, in addition to the loyality pass type
+    /// configured by the `pass_*` and `apn_*` settings.
+    pub pass_types_path: Option<String>,
+    /// JSON file with the membership tiers of the loyality pass, from lowest to highest.
+    pub membership_tiers_path: Option<String>,
+    /// How passes are sent by email, no emails are sent if not set.
+    pub email_transport: Option<EmailTransportKind>,
+    /// Sender of the emails, e.g. `Boulder Bubbletea <hello@example.com>`.
+    pub email_from: Option<String>,
+    pub email_smtp_host: Option<String>,
+    /// Defaults to 587 for `starttls`, 465 for `tls` and 25 for `none`.
+    pub email_smtp_port: Option<u16>,
+    /// `starttls`, `tls` or `none`.
+    #[serde(default)]
+    pub email_smtp_tls: SmtpTls,
+    pub email_smtp_username: Option<String>,
+    pub email_smtp_password: Option<String>,
+    pub email_file_dir: Option<String>,
+    /// JSON file with the email templates per store and locale, a built-in German template is
+    /// used for everything else.
+    pub email_templates_path: Option<String>,
+    /// Warn about certificates that expire within this many days.
+    #[serde(default = "default_certificate_expiry_warning_days")]
+    pub certificate_expiry_warning_days: u32,
     pub oidc_url: String,
+    /// Public base url of this server, used to build links for customers.
+    pub http_public_url: Option<String>,
     #[serde(default = "default_http_disable_auth")]
     pub http_disable_auth: bool,
+    #[serde(default = "default_db_repair_orphaned_passes")]
+    pub db_repair_orphaned_passes: bool,
+    /// How long a replaced auth token is still accepted, so every device can fetch the new one.
+    #[serde(default = "default_pass_auth_token_grace_period_hours")]
+    pub pass_auth_token_grace_period_hours: u32,
+    /// If set, auth tokens are rotated automatically once they are older than this.
+    pub pass_auth_token_rotation_interval_days: Option<u32>,
+    /// Where rate limit hits are counted. Use `postgres` when running multiple instances.
+    #[serde(default)]
+    pub rate_limit_store: RateLimitStoreKind,
+    /// Use the `X-Forwarded-For` header as client ip, only enable behind a reverse proxy.
+    #[serde(default)]
+    pub rate_limit_trust_forwarded_for: bool,
+    /// Rate limits are written as e.g. `20/1h`, `60/1m` or `5/30s`, `off` disables them.
+    #[serde(
+        default = "default_rate_limit_create_pass_per_ip",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_create_pass_per_ip: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_apple_log_per_ip",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_apple_log_per_ip: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_apple_registration_per_ip",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_apple_registration_per_ip: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_apple_registration_per_device",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_apple_registration_per_device: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_apple_registration_per_serial_number",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_apple_registration_per_serial_number: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_customer_pass_download_per_ip",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_customer_pass_download_per_ip: Option<RateLimitRate>,
+    #[serde(
+        default = "default_rate_limit_customer_pass_download_per_serial_number",
+        deserialize_with = "deserialize_optional_rate_limit"
+    )]
+    pub rate_limit_customer_pass_download_per_serial_number: Option<RateLimitRate>,
 }
 
 impl AppConfig {
-    pub fn from_env() -> Result<Self, envy::Error> {
-        envy::from_env()
+    pub fn rate_limit_rules(&self) -> RateLimitRules {
+        RateLimitRules {
+            create_pass_per_ip: self.rate_limit_create_pass_per_ip,
+            apple_log_per_ip: self.rate_limit_apple_log_per_ip,
+            apple_registration_per_ip: self.rate_limit_apple_registration_per_ip,
+            apple_registration_per_device: self.rate_limit_apple_registration_per_device,
+            apple_registration_per_serial_number: self
+                .rate_limit_apple_registration_per_serial_number,
+            customer_pass_download_per_ip: self.rate_limit_customer_pass_download_per_ip,
+            customer_pass_download_per_serial_number: self
+                .rate_limit_customer_pass_download_per_serial_number,
+        }
+    }
+
+    /// All configured pass types, starting with the one configured by the `pass_*` and `apn_*`
+    /// settings.
+    pub fn pass_types(&self) -> Result<Vec<PassTypeConfig>> {
+        let mut pass_types = vec![PassTypeConfig {
+            kind: PassKind::Loyality,
+            pass_type_id: self.pass_type_id.clone(),
+            signing_cert_path: self.pass_signing_cert_path.clone(),
+            signing_key_path: self.pass_signing_key_path.clone(),
+            signing_key_token: self.pass_signing_key_token.clone(),
+            wwdr_cert_path: self.pass_wwdr_cert_path.clone(),
+            apn_signing_cert_p12_path: self.apn_signing_cert_p12_path.clone(),
+            apn_signing_cert_p12_token: self.apn_signing_cert_p12_token.clone(),
+            barcode_format: self.pass_barcode_format,
+        }];
+
+        if let Some(path) = &self.pass_types_path {
+            let file = std::fs::read(path)?;
+            let more: Vec<PassTypeConfig> = serde_json::from_slice(&file)
+                .map_err(|e| Error::Other(format!("invalid pass types file {path}: {e}")))?;
+            pass_types.extend(more);
+        }
+
+        Ok(pass_types)
+    }
+
+    pub fn membership_tiers(&self) -> Result<Vec<MembershipTierConfig>> {
+        let Some(path) = &self.membership_tiers_path else {
+            return Ok(Vec::new());
+        };
+
+        let file = std::fs::read(path)?;
+        serde_json::from_slice(&file)
+            .map_err(|e| Error::Other(format!("invalid membership tiers file {path}: {e}")))
+    }
+
+    pub fn email_templates(&self) -> Result<Vec<EmailTemplateConfig>> {
+        let Some(path) = &self.email_templates_path else {
+            return Ok(Vec::new());
+        };
+
+        let file = std::fs::read(path)?;
+        serde_json::from_slice(&file)
+            .map_err(|e| Error::Other(format!("invalid email templates file {path}: {e}")))
+    }
+
+    /// The mailer with its templates, if sending emails is configured.
+    pub fn email_delivery(&self) -> Result<Option<EmailDelivery>> {
+        let Some(transport) = self.email_transport else {
+            return Ok(None);
+        };
+
+        let transport = match transport {
+            EmailTransportKind::Smtp => MailTransport::smtp(SmtpSettings {
+                host: self.email_smtp_host.as_deref().unwrap_or_default(),
+                port: self.email_smtp_port,
+                tls: self.email_smtp_tls,
+                username: self.email_smtp_username.as_deref(),
+                password: self.email_smtp_password.as_deref(),
+            })?,
+            EmailTransportKind::File => {
+                MailTransport::file(self.email_file_dir.as_deref().unwrap_or_default())?
+            }
+        };
+
+        Ok(Some(EmailDelivery {
+            mailer: Mailer::new(self.email_from.as_deref().unwrap_or_default(), transport)?,
+            templates: EmailTemplates::new(self.email_templates()?)?,
+        }))
+    }
+
+    /// Loads the configuration from the TOML file in `CONFIG_FILE`, if set, overridden by
+    /// environment variables. Secrets can also be read from the file named by their `*_FILE`
+    /// variable. All problems are collected and reported together.
+    pub fn load() -> Result<Self> {
+        let mut problems = Vec::new();
+        let mut settings = HashMap::new();
+
+        if let Ok(path) = env::var(CONFIG_FILE_VAR) {
+            match read_toml_settings(&path) {
+                Ok(file_settings) => {
+                    settings.extend(resolve_secret_files(file_settings, &mut problems))
+                }
+                Err(problem) => problems.push(problem),
+            }
+        }
+
+        let env_settings = env::vars()
+            .map(|(key, value)| (key.to_lowercase(), value))
+            .filter(|(key, _)| key != &CONFIG_FILE_VAR.to_lowercase())
+            .collect();
+        settings.extend(resolve_secret_files(env_settings, &mut problems));
+
+        let config = deserialize_settings(settings, &mut problems);
+
+        if let Some(config) = &config {
+            problems.extend(config.validate());
+        }
+
+        match config {
+            Some(config) if problems.is_empty() => Ok(config),
+            _ => Err(Error::Config(problems)),
+        }
+    }
+
+    /// Checks what can't be checked while deserializing, e.g. that the configured files exist.
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let paths = [
+            ("pass_signing_cert_path", Some(&self.pass_signing_cert_path)),
+            ("pass_signing_key_path", Some(&self.pass_signing_key_path)),
+            ("pass_wwdr_cert_path", self.pass_wwdr_cert_path.as_ref()),
+            ("pass_logo_path", Some(&self.pass_logo_path)),
+            ("pass_icon_path", Some(&self.pass_icon_path)),
+            (
+                "apn_signing_cert_p12_path",
+                Some(&self.apn_signing_cert_p12_path),
+            ),
+            ("background_image_path", Some(&self.background_image_path)),
+            ("point_image_path", Some(&self.point_image_path)),
+            ("bonus_point_image_path", Some(&self.bonus_point_image_path)),
+        ];
+        for (key, path) in paths {
+            // Empty paths are placeholders for missing settings, which are already reported.
+            if let Some(path) = path.filter(|path| !path.is_empty() && !Path::new(path).is_file()) {
+                problems.push(format!(
+                    "{}: file {path} does not exist",
+                    key.to_uppercase()
+                ));
+            }
+        }
+
+        if let Err(err) = self.pass_types() {
+            problems.push(format!("PASS_TYPES_PATH: {err}"));
+        }
+
+        if let Some(Err(err)) = self.pass_barcode_secret.as_deref().map(BarcodeSigner::new) {
+            problems.push(format!("PASS_BARCODE_SECRET: {err}"));
+        }
+
+        if let Some(secret) = self.pass_customer_view_secret.as_deref() {
+            match self.http_public_url.as_deref() {
+                Some(public_url) => {
+                    if let Err(err) = CustomerViewLinks::new(secret, public_url) {
+                        problems.push(format!("PASS_CUSTOMER_VIEW_SECRET: {err}"));
+                    }
+                }
+                None => problems.push(
+                    "PASS_CUSTOMER_VIEW_SECRET: the customer view needs HTTP_PUBLIC_URL".into(),
+                ),
+            }
+        }
+
+        if let Some(transport) = self.email_transport {
+            match self.email_from.as_deref().map(parse_mailbox) {
+                None => problems.push("EMAIL_FROM: required to send emails".into()),
+                Some(Err(err)) => problems.push(format!("EMAIL_FROM: {err}")),
+                Some(Ok(_)) => {}
+            }
+
+            match transport {
+                EmailTransportKind::Smtp if self.email_smtp_host.is_none() => {
+                    problems.push("EMAIL_SMTP_HOST: required for the smtp transport".into())
+                }
+                EmailTransportKind::File => match self.email_file_dir.as_deref() {
+                    None => problems.push("EMAIL_FILE_DIR: required for the file transport".into()),
+                    Some(dir) if !Path::new(dir).is_dir() => {
+                        problems.push(format!("EMAIL_FILE_DIR: directory {dir} does not exist"))
+                    }
+                    Some(_) => {}
+                },
+                _ => {}
+            }
+
+            if let Err(err) = self.email_templates().and_then(EmailTemplates::new) {
+                problems.push(format!("EMAIL_TEMPLATES_PATH: {err}"));
+            }
+        }
+
+        if let Err(err) = self
+            .membership_tiers()
+            .and_then(super::MembershipTiers::new)
+        {
+            problems.push(format!("MEMBERSHIP_TIERS_PATH: {err}"));
+        }
+
+        problems
+    }
+}
+
+/// Names the TOML configuration file.
+const CONFIG_FILE_VAR: &str = "CONFIG_FILE";
+
+/// Settings that can be read from a file, by setting e.g. `PASS_SIGNING_KEY_TOKEN_FILE` instead
+/// of `PASS_SIGNING_KEY_TOKEN`.
+const SECRET_SETTINGS: &[&str] = &[
+    "database_url",
+    "pass_signing_key_token",
+    "apn_signing_cert_p12_token",
+    "pass_barcode_secret",
+    "pass_customer_view_secret",
+    "email_smtp_password",
+];
+
+/// Reads the top level values of a TOML file as strings, the way they would be set as environment
+/// variables.
+fn read_toml_settings(path: &str) -> std::result::Result<HashMap<String, String>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("{CONFIG_FILE_VAR}: can't read {path}: {e}"))?;
+    let table: toml::Table =
+        toml::from_str(&content).map_err(|e| format!("{CONFIG_FILE_VAR}: invalid {path}: {e}"))?;
+
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::String(s) => s,
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                _ => {
+                    return Err(format!(
+                        "{CONFIG_FILE_VAR}: {key} in {path} must be a string, number or boolean"
+                    ))
+                }
+            };
+
+            Ok((key.to_lowercase(), value))
+        })
+        .collect()
+}
+
+/// Replaces the `*_FILE` entries of secrets with the content of the files.
+fn resolve_secret_files(
+    mut settings: HashMap<String, String>,
+    problems: &mut Vec<String>,
+) -> HashMap<String, String> {
+    for secret in SECRET_SETTINGS {
+        let Some(path) = settings.remove(&format!("{secret}_file")) else {
+            continue;
+        };
+
+        if settings.contains_key(*secret) {
+            problems.push(format!(
+                "{0} and {0}_FILE are both set, only set one of them",
+                secret.to_uppercase()
+            ));
+            continue;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(value) => {
+                settings.insert(secret.to_string(), value.trim_end().to_string());
+            }
+            Err(e) => problems.push(format!(
+                "{}_FILE: can't read {path}: {e}",
+                secret.to_uppercase()
+            )),
+        }
+    }
+
+    settings
+}
+
+/// Deserializes the settings, retrying without the offending setting after each error, so all
+/// missing and invalid settings are found instead of only the first one.
+fn deserialize_settings(
+    mut settings: HashMap<String, String>,
+    problems: &mut Vec<String>,
+) -> Option<AppConfig> {
+    loop {
+        // Errors of custom deserializers don't name the setting. The settings are read one after
+        // the other, so the last one read is the one that failed.
+        let last_key = RefCell::new(None);
+        let result = envy::from_iter::<_, AppConfig>(
+            settings
+                .iter()
+                .inspect(|(key, _)| *last_key.borrow_mut() = Some((*key).clone()))
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+
+        match result {
+            Ok(config) => return Some(config),
+            Err(envy::Error::MissingValue(key)) => {
+                problems.push(format!("{} is missing", key.to_uppercase()));
+                // All required settings are strings, so a placeholder lets the rest be checked.
+                settings.insert(key.to_string(), String::new());
+            }
+            Err(envy::Error::Custom(message)) => match last_key.into_inner() {
+                Some(key) => {
+                    problems.push(format!("{}: {message}", key.to_uppercase()));
+                    settings.remove(&key);
+                }
+                None => {
+                    problems.push(message);
+                    return None;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{deserialize_settings, PassTypeConfig};
+
+    fn pass_type_json(secrets: &str) -> String {
+        format!(
+            r#"{{
+                "kind": "coupon",
+                "pass_type_id": "pass.example.coupon",
+                "signing_cert_path": "cert.pem",
+                "signing_key_path": "key.pem",
+                "apn_signing_cert_p12_path": "apn.p12",
+                {secrets}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn reads_pass_type_secrets_from_files() {
+        let path = std::env::temp_dir().join(format!("signing-key-token-{}", std::process::id()));
+        std::fs::write(&path, "from-file\n").unwrap();
+
+        let config: PassTypeConfig = serde_json::from_str(&pass_type_json(&format!(
+            r#""signing_key_token_file": {:?}, "apn_signing_cert_p12_token": "inline""#,
+            path.to_str().unwrap()
+        )))
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.signing_key_token, "from-file");
+        assert_eq!(config.apn_signing_cert_p12_token, "inline");
+    }
+
+    #[test]
+    fn rejects_missing_and_duplicate_pass_type_secrets() {
+        assert!(serde_json::from_str::<PassTypeConfig>(&pass_type_json(
+            r#""apn_signing_cert_p12_token": "inline""#
+        ))
+        .is_err());
+        assert!(serde_json::from_str::<PassTypeConfig>(&pass_type_json(
+            r#""signing_key_token": "a", "signing_key_token_file": "b", "apn_signing_cert_p12_token": "c""#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn reports_each_invalid_and_missing_setting() {
+        let settings = HashMap::from([
+            ("http_listener_host".to_string(), "0.0.0.0:3000".to_string()),
+            ("email_smtp_port".to_string(), "not a port".to_string()),
+            (
+                "rate_limit_apple_log_per_ip".to_string(),
+                "often".to_string(),
+            ),
+        ]);
+        let mut problems = Vec::new();
+
+        assert!(deserialize_settings(settings, &mut problems).is_some());
+
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("EMAIL_SMTP_PORT: ")));
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("RATE_LIMIT_APPLE_LOG_PER_IP: ")));
+        assert!(problems.contains(&"DATABASE_URL is missing".to_string()));
+        assert!(!problems
+            .iter()
+            .any(|problem| problem.starts_with("HTTP_LISTENER_HOST")));
     }
 }
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+use crate::{
+    db::{DbPassType, DbPassTypeCoupon},
+    wallet::PassKind,
+    Error, Result,
+};
+
+use super::App;
+
+pub struct NewCoupon {
+    pub discount_text: String,
+    pub details: Option<String>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub max_redemptions: i32,
+}
+
+impl App {
+    /// Issues a new coupon and returns its serial number.
+    pub async fn add_coupon_pass(&self, coupon: NewCoupon) -> Result<String> {
+        let discount_text = coupon.discount_text.trim();
+        if discount_text.is_empty() {
+            return Err(Error::InvalidRequest("the discount text is empty".into()));
+        }
+
+        if coupon.max_redemptions < 1 {
+            return Err(Error::InvalidRequest(
+                "a coupon must be redeemable at least once".into(),
+            ));
+        }
+
+        if let (Some(valid_from), Some(valid_until)) = (coupon.valid_from, coupon.valid_until) {
+            if valid_until <= valid_from {
+                return Err(Error::InvalidRequest(
+                    "the coupon must be valid until after it is valid from".into(),
+                ));
+            }
+        }
+
+        let serial_number = uuid::Uuid::now_v7().to_string();
+
+        let pass_type = DbPassType::Coupon(DbPassTypeCoupon {
+            serial_number: serial_number.clone(),
+            discount_text: discount_text.to_string(),
+            details: coupon
+                .details
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty()),
+            valid_from: coupon.valid_from.map(|d| d.naive_utc()),
+            valid_until: coupon.valid_until.map(|d| d.naive_utc()),
+            max_redemptions: coupon.max_redemptions,
+            redemptions: 0,
+            last_redeemed_at: None,
+        });
+
+        self.insert_pass(PassKind::Coupon, pass_type, None).await?;
+
+        info!(serial_number = serial_number, "added new coupon");
+
+        Ok(serial_number)
+    }
+
+    pub async fn get_coupon_pass(&self, pass_serial_number: &str) -> Result<DbPassTypeCoupon> {
+        DbPassTypeCoupon::from_serial_number_optional(pass_serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)
+    }
+
+    /// Redeems the coupon once and pushes the updated pass, which shows the coupon as used once
+    /// all redemptions are used up.
+    pub async fn redeem_coupon_pass(&self, pass_serial_number: &str) -> Result<DbPassTypeCoupon> {
+        let now = Utc::now().naive_utc();
+
+        let Some(coupon) = DbPassTypeCoupon::redeem(pass_serial_number, now, &self.db_pool).await?
+        else {
+            // Figure out why the conditional update did not touch the coupon.
+            let coupon = self.get_coupon_pass(pass_serial_number).await?;
+
+            return Err(Error::CouponNotRedeemable(if coupon.used_up() {
+                "the coupon is used up"
+            } else if coupon.valid_from.is_some_and(|valid_from| valid_from > now) {
+                "the coupon is not valid yet"
+            } else {
+                "the coupon expired"
+            }));
+        };
+
+        info!(
+            serial_number = pass_serial_number,
+            redemptions = coupon.redemptions,
+            "redeemed coupon"
+        );
+
+        self.send_update_pass_notification(pass_serial_number)
+            .await?;
+
+        Ok(coupon)
+    }
+}
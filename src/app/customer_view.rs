@@ -0,0 +1,285 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use passes::Package;
+use sqlx::PgExecutor;
+use tracing::info;
+
+use crate::{
+    db::{
+        DbCustomerViewLink, DbGiftCardTransaction, DbPass, DbPassAuthToken, DbPassType, DbScanEvent,
+    },
+    token,
+    wallet::PassKind,
+    Error, Result,
+};
+
+use super::{pass::pass_kind, App};
+
+/// Length of the link tokens, in bytes of the HMAC-SHA256.
+const LINK_TOKEN_LENGTH: usize = 16;
+
+/// How long a customer view link can be used. Passes rendered after that get a new link.
+const LINK_VALIDITY: Duration = Duration::days(365);
+
+/// What the auth token of passes downloaded with a customer view link is derived for.
+const PASS_DOWNLOAD_AUTH_TOKEN: &str = "customer-view-pass-download";
+
+/// Number of history entries shown to the customer.
+const HISTORY_LENGTH: i64 = 20;
+
+/// Builds the links to the customer view of a pass. The links are stored hashed with an expiry
+/// and can be revoked. Their tokens are derived from the serial number and the creation time of
+/// the link, so the current link can be printed on the pass again without storing the token.
+#[derive(Debug)]
+pub struct CustomerViewLinks {
+    secret: Vec<u8>,
+    public_url: String,
+}
+
+impl CustomerViewLinks {
+    pub fn new(secret: &str, public_url: &str) -> Result<Self> {
+        if secret.len() < 32 {
+            return Err(Error::Other(
+                "the customer view secret must have at least 32 characters".into(),
+            ));
+        }
+
+        Ok(Self {
+            secret: secret.as_bytes().to_vec(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn token(&self, serial_number: &str, created_at: NaiveDateTime) -> Result<String> {
+        token::hmac(
+            &self.secret,
+            &format!(
+                "customer-view.{serial_number}.{}",
+                created_at.and_utc().timestamp_micros()
+            ),
+            LINK_TOKEN_LENGTH,
+        )
+    }
+
+    /// The path of the customer view of the pass, relative to the public url.
+    pub(super) fn path(&self, link: &DbCustomerViewLink) -> Result<String> {
+        Ok(format!(
+            "/customer/passes/{}/{}",
+            link.pass_serial_number,
+            self.token(&link.pass_serial_number, link.created_at)?
+        ))
+    }
+
+    /// The absolute link to the customer view of the pass.
+    fn url(&self, link: &DbCustomerViewLink) -> Result<String> {
+        Ok(format!("{}{}", self.public_url, self.path(link)?))
+    }
+
+    /// A new link to the customer view of the pass, which the caller has to insert.
+    pub(super) fn new_link(
+        &self,
+        serial_number: &str,
+        now: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> Result<DbCustomerViewLink> {
+        Ok(DbCustomerViewLink {
+            token_hash: token::hash(&self.token(serial_number, now)?),
+            pass_serial_number: serial_number.to_string(),
+            created_at: now,
+            expires_at,
+            revoked_at: None,
+        })
+    }
+}
+
+pub enum CustomerHistoryEntry {
+    /// The pass was scanned in a store.
+    Visit {
+        at: NaiveDateTime,
+        store: Option<String>,
+    },
+    GiftCardTransaction {
+        at: NaiveDateTime,
+        /// In minor currency units, negative when spent.
+        amount: i64,
+        balance_after: i64,
+    },
+}
+
+pub struct CustomerView {
+    pub pass: DbPass,
+    pub kind: PassKind,
+    pub pass_type: DbPassType,
+    /// Newest first.
+    pub history: Vec<CustomerHistoryEntry>,
+}
+
+impl App {
+    pub(super) fn customer_view_links(&self) -> Result<&CustomerViewLinks> {
+        self.customer_view_links
+            .as_ref()
+            .ok_or_else(|| Error::InvalidRequest("customer view links are not configured".into()))
+    }
+
+    /// Creates the first link to the customer view of a new pass, if the links are configured.
+    pub(super) async fn create_customer_view_link<'c>(
+        &self,
+        pass_serial_number: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Option<String>> {
+        let Some(links) = &self.customer_view_links else {
+            return Ok(None);
+        };
+
+        let link = links.new_link(pass_serial_number, now, now + LINK_VALIDITY)?;
+        link.insert(conn).await?;
+
+        Ok(Some(links.url(&link)?))
+    }
+
+    /// The current link to the customer view of the pass, if the links are configured. A new link
+    /// is created if the last one expired or was revoked.
+    pub(super) async fn customer_view_url(
+        &self,
+        pass_serial_number: &str,
+    ) -> Result<Option<String>> {
+        let Some(links) = &self.customer_view_links else {
+            return Ok(None);
+        };
+
+        let now = Utc::now().naive_utc();
+
+        let link = match DbCustomerViewLink::current(pass_serial_number, now, &self.db_pool).await?
+        {
+            Some(link) => link,
+            None => {
+                let link = links.new_link(pass_serial_number, now, now + LINK_VALIDITY)?;
+                link.insert(&self.db_pool).await?;
+                link
+            }
+        };
+
+        Ok(Some(links.url(&link)?))
+    }
+
+    /// Revokes the customer view links of the pass, e.g. when a photo of the back of the pass
+    /// leaked. The pass gets a new link when it is rendered the next time.
+    pub(super) async fn revoke_customer_view_links<'c>(
+        &self,
+        pass_serial_number: &str,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<()> {
+        let count =
+            DbCustomerViewLink::revoke_for_pass(pass_serial_number, Utc::now().naive_utc(), conn)
+                .await?;
+
+        if count > 0 {
+            info!(
+                serial_number = pass_serial_number,
+                count = count,
+                "revoked customer view links"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks the token of a customer view link and returns the link. Invalid, expired and
+    /// revoked tokens are reported as unknown passes, so the links don't tell which passes exist.
+    async fn verify_customer_view_token(
+        &self,
+        pass_serial_number: &str,
+        link_token: &str,
+    ) -> Result<DbCustomerViewLink> {
+        if self.customer_view_links.is_none() {
+            return Err(Error::PassNotFound);
+        }
+
+        DbCustomerViewLink::valid_from_token_hash(
+            &token::hash(link_token),
+            pass_serial_number,
+            Utc::now().naive_utc(),
+            &self.db_pool,
+        )
+        .await?
+        .ok_or(Error::PassNotFound)
+    }
+
+    /// The current state of the pass with its latest history, for the customer.
+    pub async fn customer_view(
+        &self,
+        pass_serial_number: &str,
+        link_token: &str,
+    ) -> Result<CustomerView> {
+        self.verify_customer_view_token(pass_serial_number, link_token)
+            .await?;
+
+        let pass = DbPass::from_serial_number_optional(pass_serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)?;
+        let pass_type = pass
+            .r#type
+            .from_serial_number(pass_serial_number, &self.db_pool)
+            .await?;
+
+        let history = match pass_type {
+            DbPassType::GiftCard(_) => DbGiftCardTransaction::from_serial_number(
+                pass_serial_number,
+                HISTORY_LENGTH,
+                0,
+                &self.db_pool,
+            )
+            .await?
+            .into_iter()
+            .map(|t| CustomerHistoryEntry::GiftCardTransaction {
+                at: t.created_at,
+                amount: t.amount,
+                balance_after: t.balance_after,
+            })
+            .collect(),
+            _ => DbScanEvent::from_serial_number(pass_serial_number, HISTORY_LENGTH, &self.db_pool)
+                .await?
+                .into_iter()
+                .map(|s| CustomerHistoryEntry::Visit {
+                    at: s.scanned_at,
+                    store: s.store,
+                })
+                .collect(),
+        };
+
+        Ok(CustomerView {
+            kind: pass_kind(&pass.r#type),
+            pass,
+            pass_type,
+            history,
+        })
+    }
+
+    /// Renders the pass, so the customer can add it to Wallet again. Only works as long as the
+    /// link is neither expired nor revoked. The auth token is derived from the link, so every
+    /// download with the same link shares one token instead of adding one per download.
+    pub async fn customer_pass_package(
+        &self,
+        pass_serial_number: &str,
+        link_token: &str,
+    ) -> Result<(Package, NaiveDateTime)> {
+        let link = self
+            .verify_customer_view_token(pass_serial_number, link_token)
+            .await?;
+
+        let auth_token = token::derive(link_token, PASS_DOWNLOAD_AUTH_TOKEN)?;
+
+        DbPassAuthToken {
+            token_hash: token::hash(&auth_token),
+            pass_serial_number: pass_serial_number.to_string(),
+            created_at: link.created_at,
+            needs_rotation: false,
+            expires_at: None,
+            replacement_key: None,
+        }
+        .insert_if_missing(&self.db_pool)
+        .await?;
+
+        self.pass_package(pass_serial_number, &auth_token).await
+    }
+}
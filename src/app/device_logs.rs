@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use openssl::sha::sha256;
+use tracing::info;
+
+use crate::{
+    db::{DbDeviceLog, DbDeviceLogFilter},
+    Result,
+};
+
+use super::App;
+
+/// Entries beyond this are dropped, devices only send a few at once.
+const MAX_LOG_ENTRIES_PER_REQUEST: usize = 100;
+
+/// Longer messages are truncated to this many characters.
+const MAX_LOG_MESSAGE_LENGTH: usize = 2048;
+
+/// Entries that were not received again for this long are deleted.
+const DEVICE_LOG_RETENTION: Duration = Duration::days(30);
+
+/// A log message as sent by Wallet, e.g. `[2024-04-22 11:42:01 +0200] Register task (for device
+/// 0c1d..., pass type pass.com.example, serial number 1234; with web service url ...) encountered
+/// error: Unexpected response code 401`.
+#[derive(Debug)]
+struct ParsedDeviceLog<'a> {
+    logged_at: Option<NaiveDateTime>,
+    /// The message without its timestamp.
+    message: &'a str,
+    device_library_id: Option<&'a str>,
+    pass_type_id: Option<&'a str>,
+    pass_serial_number: Option<&'a str>,
+}
+
+impl<'a> ParsedDeviceLog<'a> {
+    fn parse(line: &'a str) -> Self {
+        let line = line.trim();
+
+        let (logged_at, message) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .and_then(|(timestamp, message)| {
+                DateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S %z")
+                    .ok()
+                    .map(|logged_at| (Some(logged_at.naive_utc()), message.trim_start()))
+            })
+            .unwrap_or((None, line));
+
+        Self {
+            logged_at,
+            message,
+            device_library_id: field_value(message, "for device "),
+            pass_type_id: field_value(message, "pass type "),
+            pass_serial_number: field_value(message, "serial number "),
+        }
+    }
+}
+
+/// Returns the value following `label`, up to the next separator of Wallet's log format.
+fn field_value<'a>(message: &'a str, label: &str) -> Option<&'a str> {
+    let (_, rest) = message.split_once(label)?;
+    let value = rest
+        .split(|c: char| matches!(c, ',' | ';' | ')') || c.is_whitespace())
+        .next()?;
+
+    (!value.is_empty()).then_some(value)
+}
+
+fn truncate(message: &str) -> &str {
+    match message.char_indices().nth(MAX_LOG_MESSAGE_LENGTH) {
+        Some((index, _)) => &message[..index],
+        None => message,
+    }
+}
+
+impl App {
+    /// Stores log messages sent by Wallet. Repeats of a message are only counted, so a device
+    /// retrying a failing request doesn't fill up the table.
+    pub async fn store_device_logs(&self, logs: &[String]) -> Result<()> {
+        let now = Utc::now().naive_utc();
+
+        let mut entries: HashMap<String, DbDeviceLog> = HashMap::new();
+        for line in logs.iter().take(MAX_LOG_ENTRIES_PER_REQUEST) {
+            let parsed = ParsedDeviceLog::parse(line);
+            if parsed.message.is_empty() {
+                continue;
+            }
+
+            let message_hash = hex::encode(sha256(parsed.message.as_bytes()));
+            entries
+                .entry(message_hash.clone())
+                .and_modify(|entry| {
+                    entry.occurrences += 1;
+                    if let Some(logged_at) = parsed.logged_at {
+                        entry.first_logged_at = Some(
+                            entry
+                                .first_logged_at
+                                .map_or(logged_at, |first| first.min(logged_at)),
+                        );
+                        entry.last_logged_at = Some(
+                            entry
+                                .last_logged_at
+                                .map_or(logged_at, |last| last.max(logged_at)),
+                        );
+                    }
+                })
+                .or_insert_with(|| DbDeviceLog {
+                    message_hash,
+                    message: truncate(parsed.message).to_string(),
+                    device_library_id: parsed.device_library_id.map(str::to_string),
+                    pass_type_id: parsed.pass_type_id.map(str::to_string),
+                    pass_serial_number: parsed.pass_serial_number.map(str::to_string),
+                    first_logged_at: parsed.logged_at,
+                    last_logged_at: parsed.logged_at,
+                    first_seen_at: now,
+                    last_seen_at: now,
+                    occurrences: 1,
+                });
+        }
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        for entry in entries.values() {
+            entry.upsert(&mut *transaction).await?;
+        }
+
+        let deleted =
+            DbDeviceLog::delete_last_seen_before(now - DEVICE_LOG_RETENTION, &mut *transaction)
+                .await?;
+
+        transaction.commit().await?;
+
+        info!(
+            count = logs.len(),
+            distinct = entries.len(),
+            "stored device logs"
+        );
+        if deleted > 0 {
+            info!(count = deleted, "deleted old device logs");
+        }
+
+        Ok(())
+    }
+
+    pub async fn search_device_logs(
+        &self,
+        filter: &DbDeviceLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<DbDeviceLog>, i64)> {
+        Ok(DbDeviceLog::search(filter, limit, offset, &self.db_pool).await?)
+    }
+}
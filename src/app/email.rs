@@ -0,0 +1,442 @@
+use std::{collections::HashSet, time::Duration as StdDuration};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use tracing::{error, info, warn};
+
+use sqlx::PgExecutor;
+
+use crate::{
+    db::{DbEmailOutbox, DbPass},
+    email::{parse_mailbox, EmailAttachment, Mailer, OutgoingEmail},
+    member_code, token,
+    wallet::package_bytes,
+    Error, Result,
+};
+
+use super::{App, EmailTemplateConfig};
+
+/// Locale of the built-in template, and the fallback when no template matches.
+const DEFAULT_LOCALE: &str = "de";
+
+/// How often the outbox is checked for due emails.
+const EMAIL_QUEUE_INTERVAL: StdDuration = StdDuration::from_secs(15);
+
+/// Upper limit of emails sent with one check of the outbox.
+const EMAIL_QUEUE_BATCH_SIZE: i64 = 20;
+
+/// An email is given up after this many failed attempts.
+const MAX_EMAIL_ATTEMPTS: i32 = 6;
+
+/// How long a claimed email is not picked up by other instances.
+const EMAIL_SEND_LEASE: Duration = Duration::minutes(5);
+
+/// Waits 1, 4, 16, 64 and 256 minutes between the attempts.
+fn retry_delay(attempts: i32) -> Duration {
+    Duration::minutes(4_i64.pow(attempts.clamp(1, MAX_EMAIL_ATTEMPTS) as u32 - 1))
+}
+
+/// What happens with a queued email after a failed attempt.
+#[derive(Debug, PartialEq, Eq)]
+enum FailedAttempt {
+    Retry { next_attempt_at: NaiveDateTime },
+    GiveUp,
+}
+
+impl FailedAttempt {
+    /// Decides after the `attempts`-th failed attempt at `now`.
+    fn after(attempts: i32, now: NaiveDateTime) -> Self {
+        if attempts >= MAX_EMAIL_ATTEMPTS {
+            Self::GiveUp
+        } else {
+            Self::Retry {
+                next_attempt_at: now + retry_delay(attempts),
+            }
+        }
+    }
+}
+
+const DEFAULT_SUBJECT: &str = "Dein Boulder Bubbletea Pass";
+const DEFAULT_BODY: &str = "Hallo {pass_holder_name},
+
+im Anhang findest du deinen Boulder Bubbletea Pass. Öffne ihn auf deinem iPhone, um ihn zu Apple \
+Wallet hinzuzufügen.
+
+Deine Mitgliedsnummer: {member_code}
+{customer_view_url}
+Bis bald!";
+
+/// The email templates per store and locale. Placeholders in subject and body are replaced with
+/// the values of the pass: `{pass_holder_name}`, `{member_code}`, `{customer_view_url}` and
+/// `{store}`.
+#[derive(Debug, Default)]
+pub struct EmailTemplates {
+    templates: Vec<EmailTemplateConfig>,
+}
+
+impl EmailTemplates {
+    pub fn new(templates: Vec<EmailTemplateConfig>) -> Result<Self> {
+        let mut keys = HashSet::new();
+
+        for template in &templates {
+            if template.subject.trim().is_empty() || template.body.trim().is_empty() {
+                return Err(Error::Other(format!(
+                    "template for locale {} needs a subject and a body",
+                    template.locale
+                )));
+            }
+
+            if !keys.insert((template.store.as_deref(), template.locale.as_str())) {
+                return Err(Error::Other(format!(
+                    "template for store {:?} and locale {} is configured more than once",
+                    template.store, template.locale
+                )));
+            }
+        }
+
+        Ok(Self { templates })
+    }
+
+    /// The template for the store and locale. Falls back to the language of the locale, e.g. `de`
+    /// for `de-AT`, then to the default locale, and prefers templates of the store over the ones
+    /// for all stores.
+    fn find(&self, store: Option<&str>, locale: &str) -> Option<&EmailTemplateConfig> {
+        let language = locale.split(['-', '_']).next().unwrap_or(locale);
+
+        [locale, language, DEFAULT_LOCALE]
+            .into_iter()
+            .flat_map(|locale| [(store, locale), (None, locale)])
+            .find_map(|(store, locale)| {
+                self.templates.iter().find(|template| {
+                    template.store.as_deref() == store
+                        && template.locale.eq_ignore_ascii_case(locale)
+                })
+            })
+    }
+
+    /// Renders subject and body of the email with the pass.
+    fn render(
+        &self,
+        store: Option<&str>,
+        locale: &str,
+        values: &[(&str, &str)],
+    ) -> (String, String) {
+        let (subject, body) = match self.find(store, locale) {
+            Some(template) => (template.subject.as_str(), template.body.as_str()),
+            None => (DEFAULT_SUBJECT, DEFAULT_BODY),
+        };
+
+        let fill = |text: &str| {
+            values
+                .iter()
+                .fold(text.to_string(), |text, (placeholder, value)| {
+                    text.replace(&format!("{{{placeholder}}}"), value)
+                })
+        };
+
+        (fill(subject), fill(body))
+    }
+}
+
+/// The mailer with the templates of the emails.
+#[derive(Debug)]
+pub struct EmailDelivery {
+    pub mailer: Mailer,
+    pub templates: EmailTemplates,
+}
+
+/// Where and how the pass is sent to the customer.
+pub struct PassEmail {
+    pub to: String,
+    /// Selects the template, e.g. the store the customer signed up in.
+    pub store: Option<String>,
+    /// Selects the template, e.g. `de` or `en-GB`.
+    pub locale: Option<String>,
+}
+
+impl App {
+    fn email_delivery(&self) -> Result<&EmailDelivery> {
+        self.email_delivery
+            .as_ref()
+            .ok_or_else(|| Error::InvalidRequest("email delivery is not configured".into()))
+    }
+
+    /// Checks that the pass can be sent by email, before it is created.
+    pub(super) fn check_pass_email(&self, email: &PassEmail) -> Result<()> {
+        self.email_delivery()?;
+        parse_mailbox(&email.to)?;
+
+        Ok(())
+    }
+
+    /// Queues an email with the pass attached, in the transaction that creates the pass. The
+    /// pass is rendered with a new auth token when the email is sent.
+    pub(super) async fn queue_pass_email<'c>(
+        &self,
+        pass: &DbPass,
+        pass_holder_name: &str,
+        customer_view_url: Option<&str>,
+        email: &PassEmail,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<DbEmailOutbox> {
+        let delivery = self.email_delivery()?;
+
+        let member_code = member_code::display(&pass.member_code);
+        let store = email
+            .store
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+
+        let (subject, body) = delivery.templates.render(
+            store,
+            email.locale.as_deref().unwrap_or(DEFAULT_LOCALE),
+            &[
+                ("pass_holder_name", pass_holder_name),
+                ("member_code", &member_code),
+                ("customer_view_url", customer_view_url.unwrap_or_default()),
+                ("store", store.unwrap_or_default()),
+            ],
+        );
+
+        let queued = DbEmailOutbox::insert(
+            Some(&pass.serial_number),
+            email.to.trim(),
+            &subject,
+            &body,
+            Utc::now().naive_utc(),
+            conn,
+        )
+        .await?;
+
+        info!(
+            serial_number = pass.serial_number,
+            email_id = queued.id,
+            "queued pass email"
+        );
+
+        Ok(queued)
+    }
+
+    /// Sends the queued emails until the task is stopped. Failed emails are retried with
+    /// increasing delays.
+    pub async fn run_email_queue(&self) {
+        if self.email_delivery.is_none() {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(EMAIL_QUEUE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.send_due_emails().await {
+                error!("sending queued emails failed: {}", err);
+            }
+        }
+    }
+
+    async fn send_due_emails(&self) -> Result<()> {
+        let now = Utc::now().naive_utc();
+
+        let emails = DbEmailOutbox::claim_due(
+            now,
+            now + EMAIL_SEND_LEASE,
+            EMAIL_QUEUE_BATCH_SIZE,
+            &self.db_pool,
+        )
+        .await?;
+
+        for email in emails {
+            let id = email.id;
+            let attempts = email.attempts + 1;
+
+            let err = match self.send_queued_email(email).await {
+                Ok(auth_token) => {
+                    // The auth token of the attached pass is only stored once the email was
+                    // sent, so failed attempts don't leave tokens behind.
+                    let mut transaction = self.db_pool.begin().await?;
+
+                    if let Some((serial_number, auth_token)) = auth_token {
+                        self.insert_auth_token(&serial_number, &auth_token, &mut transaction)
+                            .await?;
+                    }
+                    DbEmailOutbox::mark_sent(id, Utc::now().naive_utc(), &mut *transaction).await?;
+
+                    transaction.commit().await?;
+
+                    info!(email_id = id, attempts = attempts, "sent queued email");
+                    continue;
+                }
+                Err(err) => err,
+            };
+
+            let now = Utc::now().naive_utc();
+
+            match FailedAttempt::after(attempts, now) {
+                FailedAttempt::GiveUp => {
+                    DbEmailOutbox::give_up(id, &err.to_string(), now, &self.db_pool).await?;
+
+                    error!(
+                        email_id = id,
+                        attempts = attempts,
+                        "gave up queued email: {}",
+                        err
+                    );
+                }
+                FailedAttempt::Retry { next_attempt_at } => {
+                    DbEmailOutbox::reschedule(id, &err.to_string(), next_attempt_at, &self.db_pool)
+                        .await?;
+
+                    warn!(
+                        email_id = id,
+                        attempts = attempts,
+                        next_attempt_at = next_attempt_at.to_string(),
+                        "sending queued email failed: {}",
+                        err
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends the email with the pass rendered with a new auth token. Returns the serial number of
+    /// the pass with the auth token, which the caller has to store.
+    async fn send_queued_email(&self, email: DbEmailOutbox) -> Result<Option<(String, String)>> {
+        let delivery = self.email_delivery()?;
+
+        let (attachment, auth_token) = match email.pass_serial_number {
+            Some(serial_number) => {
+                let auth_token = token::generate()?;
+                let (mut package, _) = self.pass_package(&serial_number, &auth_token).await?;
+
+                (
+                    Some(EmailAttachment {
+                        filename: "pass.pkpass".into(),
+                        content_type: "application/vnd.apple.pkpass",
+                        data: package_bytes(&mut package)?,
+                    }),
+                    Some((serial_number, auth_token)),
+                )
+            }
+            None => (None, None),
+        };
+
+        delivery
+            .mailer
+            .send(OutgoingEmail {
+                to: email.recipient,
+                subject: email.subject,
+                body: email.body,
+                attachment,
+            })
+            .await?;
+
+        Ok(auth_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::{EmailTemplates, FailedAttempt, MAX_EMAIL_ATTEMPTS};
+    use crate::app::EmailTemplateConfig;
+
+    fn template(store: Option<&str>, locale: &str) -> EmailTemplateConfig {
+        EmailTemplateConfig {
+            store: store.map(Into::into),
+            locale: locale.into(),
+            subject: format!("{store:?} {locale}"),
+            body: "Hallo {pass_holder_name}".into(),
+        }
+    }
+
+    fn found(templates: &EmailTemplates, store: Option<&str>, locale: &str) -> Option<String> {
+        templates
+            .find(store, locale)
+            .map(|template| template.subject.clone())
+    }
+
+    #[test]
+    fn finds_templates_with_fallbacks() {
+        let templates = EmailTemplates::new(vec![
+            template(None, "de"),
+            template(None, "en"),
+            template(Some("Berlin"), "de"),
+            template(Some("Berlin"), "en-GB"),
+        ])
+        .unwrap();
+
+        // The store's template for the exact locale, then for the language.
+        assert_eq!(
+            found(&templates, Some("Berlin"), "en-GB").as_deref(),
+            Some("Some(\"Berlin\") en-GB")
+        );
+        assert_eq!(
+            found(&templates, Some("Berlin"), "de-AT").as_deref(),
+            Some("Some(\"Berlin\") de")
+        );
+        // The template for all stores is preferred over the store's default locale.
+        assert_eq!(
+            found(&templates, Some("Berlin"), "en-US").as_deref(),
+            Some("None en")
+        );
+        // Unknown stores use the templates for all stores, unknown locales the default locale.
+        assert_eq!(
+            found(&templates, Some("Hamburg"), "en").as_deref(),
+            Some("None en")
+        );
+        assert_eq!(found(&templates, None, "fr").as_deref(), Some("None de"));
+        assert_eq!(
+            found(&templates, Some("Berlin"), "fr").as_deref(),
+            Some("Some(\"Berlin\") de")
+        );
+        assert_eq!(found(&EmailTemplates::default(), None, "de"), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_and_empty_templates() {
+        assert!(EmailTemplates::new(vec![template(None, "de"), template(None, "de")]).is_err());
+
+        let mut empty = template(None, "de");
+        empty.body = " ".into();
+        assert!(EmailTemplates::new(vec![empty]).is_err());
+    }
+
+    #[test]
+    fn retries_failed_emails_with_increasing_delays() {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        for (attempts, minutes) in [(1, 1), (2, 4), (3, 16), (4, 64), (5, 256)] {
+            assert_eq!(
+                FailedAttempt::after(attempts, now),
+                FailedAttempt::Retry {
+                    next_attempt_at: now + Duration::minutes(minutes)
+                },
+                "after {attempts} attempts"
+            );
+        }
+    }
+
+    #[test]
+    fn gives_up_after_the_last_attempt() {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        assert_eq!(
+            FailedAttempt::after(MAX_EMAIL_ATTEMPTS, now),
+            FailedAttempt::GiveUp
+        );
+        assert_eq!(
+            FailedAttempt::after(MAX_EMAIL_ATTEMPTS + 1, now),
+            FailedAttempt::GiveUp
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use tracing::info;
+
+use crate::{
+    db::{DbEvent, DbPass, DbPassTypeEventTicket, DbPassTypeHelper},
+    member_code,
+    wallet::PassKind,
+    Error, Result,
+};
+
+use super::App;
+
+/// Upper limit of tickets issued with one request, to keep the transaction short.
+pub const MAX_TICKETS_PER_ISSUANCE: usize = 200;
+
+/// How long after the end of the event, or its start if it has no end, the ticket can still be
+/// downloaded.
+const TICKET_DOWNLOAD_GRACE_PERIOD: Duration = Duration::days(1);
+
+pub struct NewEvent {
+    pub name: String,
+    pub venue: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+pub struct NewEventTicket {
+    pub attendee_name: Option<String>,
+    /// Seat or time slot of the attendee.
+    pub seat: Option<String>,
+}
+
+pub struct IssuedEventTicket {
+    pub ticket: DbPassTypeEventTicket,
+    /// Path of the link with which the attendee adds the ticket to Wallet, relative to the public
+    /// url. The link can be used repeatedly until the event is over.
+    pub download_path: String,
+    pub download_expires_at: DateTime<Utc>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+impl App {
+    pub async fn add_event(&self, event: NewEvent, created_by: &str) -> Result<DbEvent> {
+        let name = event.name.trim();
+        if name.is_empty() {
+            return Err(Error::InvalidRequest("the name must not be empty".into()));
+        }
+
+        if event
+            .ends_at
+            .is_some_and(|ends_at| ends_at < event.starts_at)
+        {
+            return Err(Error::InvalidRequest(
+                "the event must not end before it starts".into(),
+            ));
+        }
+
+        let event = DbEvent {
+            id: uuid::Uuid::now_v7().to_string(),
+            name: name.to_string(),
+            venue: non_empty(event.venue),
+            starts_at: event.starts_at.naive_utc(),
+            ends_at: event.ends_at.map(|t| t.naive_utc()),
+            created_at: Utc::now().naive_utc(),
+            created_by: created_by.to_string(),
+        };
+
+        event.insert(&self.db_pool).await?;
+
+        info!(
+            event_id = event.id,
+            created_by = created_by,
+            "added new event"
+        );
+
+        Ok(event)
+    }
+
+    pub async fn get_event(&self, event_id: &str) -> Result<DbEvent> {
+        DbEvent::from_id(event_id, &self.db_pool)
+            .await?
+            .ok_or(Error::EventNotFound)
+    }
+
+    /// Returns how many tickets were issued for the event and how many of them checked in.
+    pub async fn event_ticket_counts(&self, event_id: &str) -> Result<(i64, i64)> {
+        Ok(DbEvent::ticket_counts(event_id, &self.db_pool).await?)
+    }
+
+    /// Issues a ticket for each entry of `tickets`. Either all tickets are issued or none.
+    ///
+    /// The tickets are not rendered yet, the attendees download them with the link to the
+    /// customer view of their ticket. Tickets are often sent weeks ahead and mail scanners open
+    /// links before the attendee does, so the link stays valid until the event is over.
+    pub async fn issue_event_tickets(
+        &self,
+        event_id: &str,
+        tickets: Vec<NewEventTicket>,
+        created_by: &str,
+    ) -> Result<Vec<IssuedEventTicket>> {
+        if tickets.is_empty() || tickets.len() > MAX_TICKETS_PER_ISSUANCE {
+            return Err(Error::InvalidRequest(format!(
+                "between 1 and {MAX_TICKETS_PER_ISSUANCE} tickets can be issued at once"
+            )));
+        }
+
+        let event = self.get_event(event_id).await?;
+        let links = self.customer_view_links()?;
+
+        let pass_types = self.pass_types();
+        let pass_type_config = pass_types.for_kind(PassKind::EventTicket)?;
+
+        let now = Utc::now();
+        let download_expires_at =
+            event.ends_at.unwrap_or(event.starts_at) + TICKET_DOWNLOAD_GRACE_PERIOD;
+        let mut issued = Vec::with_capacity(tickets.len());
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        for ticket in tickets {
+            let serial_number = uuid::Uuid::now_v7().to_string();
+
+            let ticket = DbPassTypeEventTicket {
+                serial_number: serial_number.clone(),
+                event_id: event_id.to_string(),
+                attendee_name: non_empty(ticket.attendee_name),
+                seat: non_empty(ticket.seat),
+                checked_in_at: None,
+            };
+
+            DbPass {
+                serial_number: serial_number.clone(),
+                pass_type_id: pass_type_config.pass_type_identifier.clone(),
+                created_at: now.naive_utc(),
+                last_updated_at: now.naive_utc(),
+                r#type: DbPassTypeHelper::EventTicket,
+                member_code: member_code::generate()?,
+            }
+            .insert(&mut *transaction)
+            .await?;
+            ticket.insert(&mut *transaction).await?;
+
+            let link = links.new_link(&serial_number, now.naive_utc(), download_expires_at)?;
+            link.insert(&mut *transaction).await?;
+
+            issued.push(IssuedEventTicket {
+                ticket,
+                download_path: format!("{}/pass.pkpass", links.path(&link)?),
+                download_expires_at: Utc.from_utc_datetime(&download_expires_at),
+            });
+        }
+
+        transaction.commit().await?;
+
+        info!(
+            event_id = event_id,
+            count = issued.len(),
+            created_by = created_by,
+            "issued event tickets"
+        );
+
+        Ok(issued)
+    }
+
+    pub async fn get_event_ticket_pass(
+        &self,
+        pass_serial_number: &str,
+    ) -> Result<DbPassTypeEventTicket> {
+        DbPassTypeEventTicket::from_serial_number_optional(pass_serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)
+    }
+
+    /// Marks the ticket as used, fails with [`Error::TicketAlreadyCheckedIn`] if it was used
+    /// before.
+    pub async fn check_in_event_ticket(
+        &self,
+        pass_serial_number: &str,
+        checked_in_by: &str,
+    ) -> Result<DbPassTypeEventTicket> {
+        let Some(ticket) = DbPassTypeEventTicket::check_in(
+            pass_serial_number,
+            Utc::now().naive_utc(),
+            &self.db_pool,
+        )
+        .await?
+        else {
+            // Figure out why the conditional update did not touch the ticket.
+            let ticket = self.get_event_ticket_pass(pass_serial_number).await?;
+
+            return Err(match ticket.checked_in_at {
+                Some(checked_in_at) => Error::TicketAlreadyCheckedIn { checked_in_at },
+                None => Error::Unknown,
+            });
+        };
+
+        info!(
+            serial_number = pass_serial_number,
+            event_id = ticket.event_id,
+            checked_in_by = checked_in_by,
+            "checked in event ticket"
+        );
+
+        self.send_update_pass_notification(pass_serial_number)
+            .await?;
+
+        Ok(ticket)
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::Utc;
+use tracing::info;
+
+use crate::{
+    db::{DbGiftCardTransaction, DbPassType, DbPassTypeGiftCard},
+    wallet::PassKind,
+    Error, Result,
+};
+
+use super::App;
+
+/// Upper limit of a single booking, in minor currency units, to catch typos.
+const MAX_GIFT_CARD_AMOUNT: i64 = 1_000_000;
+
+/// Checks the amount of a top-up or spending, before it is given a sign.
+fn check_amount(amount: i64) -> Result<()> {
+    if !(1..=MAX_GIFT_CARD_AMOUNT).contains(&amount) {
+        return Err(Error::InvalidRequest(format!(
+            "the amount must be between 1 and {MAX_GIFT_CARD_AMOUNT}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub struct NewGiftCard {
+    /// In minor currency units, e.g. cents.
+    pub initial_balance: i64,
+    /// ISO 4217 code, e.g. `EUR`.
+    pub currency: String,
+    pub pass_holder_name: Option<String>,
+}
+
+impl App {
+    /// Issues a new gift card and returns its serial number.
+    pub async fn add_gift_card_pass(&self, gift_card: NewGiftCard) -> Result<String> {
+        if !(0..=MAX_GIFT_CARD_AMOUNT).contains(&gift_card.initial_balance) {
+            return Err(Error::InvalidRequest(format!(
+                "the initial balance must be between 0 and {MAX_GIFT_CARD_AMOUNT}"
+            )));
+        }
+
+        if gift_card.currency.len() != 3
+            || !gift_card.currency.chars().all(|c| c.is_ascii_uppercase())
+        {
+            return Err(Error::InvalidRequest(format!(
+                "{} is not an ISO 4217 currency code",
+                gift_card.currency
+            )));
+        }
+
+        let serial_number = uuid::Uuid::now_v7().to_string();
+
+        let pass_type = DbPassType::GiftCard(DbPassTypeGiftCard {
+            serial_number: serial_number.clone(),
+            balance: gift_card.initial_balance,
+            currency: gift_card.currency,
+            pass_holder_name: gift_card
+                .pass_holder_name
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty()),
+            last_used_at: None,
+        });
+
+        self.insert_pass(PassKind::GiftCard, pass_type, None)
+            .await?;
+
+        info!(serial_number = serial_number, "added new gift card");
+
+        Ok(serial_number)
+    }
+
+    pub async fn get_gift_card_pass(&self, pass_serial_number: &str) -> Result<DbPassTypeGiftCard> {
+        DbPassTypeGiftCard::from_serial_number_optional(pass_serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)
+    }
+
+    pub async fn gift_card_transactions(
+        &self,
+        pass_serial_number: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DbGiftCardTransaction>> {
+        self.get_gift_card_pass(pass_serial_number).await?;
+
+        Ok(DbGiftCardTransaction::from_serial_number(
+            pass_serial_number,
+            limit,
+            offset,
+            &self.db_pool,
+        )
+        .await?)
+    }
+
+    pub async fn top_up_gift_card(
+        &self,
+        pass_serial_number: &str,
+        amount: i64,
+        note: Option<&str>,
+        created_by: &str,
+    ) -> Result<DbPassTypeGiftCard> {
+        check_amount(amount)?;
+
+        self.change_gift_card_balance(pass_serial_number, amount, note, created_by)
+            .await
+    }
+
+    /// Takes `amount` from the balance, fails with [`Error::InsufficientBalance`] if the balance
+    /// is too low.
+    pub async fn spend_gift_card(
+        &self,
+        pass_serial_number: &str,
+        amount: i64,
+        note: Option<&str>,
+        created_by: &str,
+    ) -> Result<DbPassTypeGiftCard> {
+        check_amount(amount)?;
+
+        self.change_gift_card_balance(pass_serial_number, -amount, note, created_by)
+            .await
+    }
+
+    /// Books `amount`, positive for top-ups and negative for spendings, checked by the callers.
+    async fn change_gift_card_balance(
+        &self,
+        pass_serial_number: &str,
+        amount: i64,
+        note: Option<&str>,
+        created_by: &str,
+    ) -> Result<DbPassTypeGiftCard> {
+        let note = note.map(str::trim).filter(|n| !n.is_empty());
+
+        let Some(gift_card) = DbPassTypeGiftCard::change_balance(
+            pass_serial_number,
+            amount,
+            note,
+            created_by,
+            Utc::now().naive_utc(),
+            &self.db_pool,
+        )
+        .await?
+        else {
+            // Figure out why the conditional update did not touch the gift card.
+            let gift_card = self.get_gift_card_pass(pass_serial_number).await?;
+
+            return Err(Error::InsufficientBalance {
+                balance: gift_card.balance,
+            });
+        };
+
+        info!(
+            serial_number = pass_serial_number,
+            amount = amount,
+            balance = gift_card.balance,
+            created_by = created_by,
+            "changed gift card balance"
+        );
+
+        self.send_update_pass_notification(pass_serial_number)
+            .await?;
+
+        Ok(gift_card)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_amount, MAX_GIFT_CARD_AMOUNT};
+
+    #[test]
+    fn accepts_positive_amounts() {
+        assert!(check_amount(1).is_ok());
+        assert!(check_amount(MAX_GIFT_CARD_AMOUNT).is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_negative_and_too_large_amounts() {
+        for amount in [0, -1, -500, i64::MIN, MAX_GIFT_CARD_AMOUNT + 1, i64::MAX] {
+            assert!(check_amount(amount).is_err(), "{amount} was accepted");
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use tracing::info;
 
-use crate::{db::DbPassTypeLoyality, Error, Result};
+use crate::{
+    db::{DbLoyalityPassSearch, DbPassHolderUpdate, DbPassTypeLoyality},
+    Error, Result,
+};
 
 use super::App;
 
@@ -9,42 +12,43 @@ impl App {
         &self,
         pass_serial_number: &str,
         points: i32,
-    ) -> Result<()> {
-        let pass =
-            DbPassTypeLoyality::from_serial_number_optional(pass_serial_number, &self.db_pool)
-                .await?
-                .ok_or(Error::PassNotFound)?;
-
-        if (pass.total_points - pass.current_points) < points || points == 0 {
+    ) -> Result<DbPassTypeLoyality> {
+        if points <= 0 {
             return Err(Error::InvalidAmountOfPoints);
         }
 
-        DbPassTypeLoyality::add_points(pass_serial_number, points, &self.db_pool).await?;
+        let pass = match DbPassTypeLoyality::add_points(pass_serial_number, points, &self.db_pool)
+            .await?
+        {
+            Some(pass) => pass,
+            None => return Err(self.loyality_update_error(pass_serial_number).await?),
+        };
+        let pass = self.promote_membership_tier(pass).await?;
 
         self.send_update_pass_notification(pass_serial_number)
             .await?;
 
-        Ok(())
+        Ok(pass)
     }
 
-    pub async fn pass_loyality_redeem_bonus(&self, pass_serial_number: &str) -> Result<()> {
-        let pass =
-            DbPassTypeLoyality::from_serial_number_optional(pass_serial_number, &self.db_pool)
-                .await?
-                .ok_or(Error::PassNotFound)?;
-
-        if pass.total_points != pass.current_points {
-            return Err(Error::InvalidAmountOfPoints);
-        }
-
-        DbPassTypeLoyality::redeem_bonus(pass_serial_number, &self.db_pool).await?;
+    pub async fn pass_loyality_redeem_bonus(
+        &self,
+        pass_serial_number: &str,
+    ) -> Result<DbPassTypeLoyality> {
+        let pass = match DbPassTypeLoyality::redeem_bonus(pass_serial_number, &self.db_pool).await?
+        {
+            Some(pass) => pass,
+            None => return Err(self.loyality_update_error(pass_serial_number).await?),
+        };
 
         info!("Pass {pass_serial_number} successfully redeemed bonus");
 
+        let pass = self.promote_membership_tier(pass).await?;
+
         self.send_update_pass_notification(pass_serial_number)
             .await?;
 
-        Ok(())
+        Ok(pass)
     }
 
     pub async fn get_loyality_pass(&self, pass_serial_number: &str) -> Result<DbPassTypeLoyality> {
@@ -52,4 +56,71 @@ impl App {
             .await?
             .ok_or(Error::PassNotFound)
     }
+
+    /// Changes the pass holder details and pushes the re-rendered pass to the registered devices.
+    pub async fn update_loyality_pass_holder(
+        &self,
+        pass_serial_number: &str,
+        mut update: DbPassHolderUpdate,
+    ) -> Result<DbPassTypeLoyality> {
+        if let Some(name) = &mut update.pass_holder_name {
+            *name = name.trim().to_string();
+
+            if name.is_empty() {
+                return Err(Error::InvalidRequest(
+                    "the pass holder name is empty".into(),
+                ));
+            }
+        }
+
+        // Empty contact fields are treated as removed.
+        for field in [&mut update.pass_holder_email, &mut update.pass_holder_phone]
+            .into_iter()
+            .flatten()
+        {
+            *field = field
+                .take()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty());
+        }
+
+        if let Some(Some(email)) = &update.pass_holder_email {
+            if !email.contains('@') {
+                return Err(Error::InvalidRequest(format!(
+                    "{email} is not a valid email"
+                )));
+            }
+        }
+
+        let pass =
+            DbPassTypeLoyality::update_pass_holder(pass_serial_number, &update, &self.db_pool)
+                .await?
+                .ok_or(Error::PassNotFound)?;
+
+        info!("Pass {pass_serial_number} changed its pass holder details");
+
+        self.send_update_pass_notification(pass_serial_number)
+            .await?;
+
+        Ok(pass)
+    }
+
+    pub async fn search_loyality_passes(
+        &self,
+        search: &DbLoyalityPassSearch,
+    ) -> Result<(Vec<DbPassTypeLoyality>, i64)> {
+        Ok(DbPassTypeLoyality::search(search, &self.db_pool).await?)
+    }
+
+    /// Figures out why a conditional update did not touch any row.
+    async fn loyality_update_error(&self, pass_serial_number: &str) -> Result<Error> {
+        Ok(
+            match DbPassTypeLoyality::from_serial_number_optional(pass_serial_number, &self.db_pool)
+                .await?
+            {
+                Some(_) => Error::InvalidAmountOfPoints,
+                None => Error::PassNotFound,
+            },
+        )
+    }
 }
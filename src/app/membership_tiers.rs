@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use tracing::info;
+
+use crate::{db::DbPassTypeLoyality, Error, Result};
+
+use super::{App, MembershipTierConfig};
+
+/// The configured membership tiers, from lowest to highest.
+#[derive(Debug, Default)]
+pub struct MembershipTiers {
+    tiers: Vec<MembershipTierConfig>,
+}
+
+impl MembershipTiers {
+    pub fn new(tiers: Vec<MembershipTierConfig>) -> Result<Self> {
+        let mut ids = HashSet::new();
+
+        for tier in &tiers {
+            if tier.id.is_empty() || tier.id.len() > 64 {
+                return Err(Error::Other(format!(
+                    "tier id {:?} must have between 1 and 64 characters",
+                    tier.id
+                )));
+            }
+
+            if !ids.insert(tier.id.as_str()) {
+                return Err(Error::Other(format!(
+                    "tier {} is configured more than once",
+                    tier.id
+                )));
+            }
+
+            if tier.min_redemptions.is_none() && tier.min_lifetime_points.is_none() {
+                return Err(Error::Other(format!(
+                    "tier {} needs min_redemptions or min_lifetime_points",
+                    tier.id
+                )));
+            }
+        }
+
+        Ok(Self { tiers })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&MembershipTierConfig> {
+        self.tiers.iter().find(|tier| tier.id == id)
+    }
+
+    fn rank(&self, id: &str) -> Option<usize> {
+        self.tiers.iter().position(|tier| tier.id == id)
+    }
+
+    /// The highest tier whose thresholds the pass meets.
+    pub fn reached_by(&self, pass: &DbPassTypeLoyality) -> Option<&MembershipTierConfig> {
+        self.tiers.iter().rev().find(|tier| {
+            tier.min_redemptions
+                .is_some_and(|min| pass.already_redeemed >= min)
+                || tier
+                    .min_lifetime_points
+                    .is_some_and(|min| pass.lifetime_points >= min)
+        })
+    }
+
+    /// The tier the pass has to be promoted to, if it reached a higher one than its current.
+    /// Passes are never demoted, e.g. when the thresholds are raised.
+    pub fn promotion_for(&self, pass: &DbPassTypeLoyality) -> Option<&MembershipTierConfig> {
+        let reached = self.reached_by(pass)?;
+        let current_rank = pass.tier.as_deref().and_then(|id| self.rank(id));
+
+        match current_rank {
+            Some(current_rank) if current_rank >= self.rank(&reached.id)? => None,
+            _ => Some(reached),
+        }
+    }
+}
+
+impl App {
+    /// Moves the pass into the highest membership tier it reached. The caller notifies the
+    /// devices, the pass shows a change message for the new tier.
+    pub(super) async fn promote_membership_tier(
+        &self,
+        pass: DbPassTypeLoyality,
+    ) -> Result<DbPassTypeLoyality> {
+        let Some(tier) = self.membership_tiers.promotion_for(&pass) else {
+            return Ok(pass);
+        };
+
+        let promoted = DbPassTypeLoyality::set_tier(
+            &pass.serial_number,
+            &tier.id,
+            Utc::now().naive_utc(),
+            &self.db_pool,
+        )
+        .await?
+        .ok_or(Error::PassNotFound)?;
+
+        info!(
+            serial_number = pass.serial_number,
+            previous_tier = pass.tier,
+            tier = tier.id,
+            "promoted pass to a new membership tier"
+        );
+
+        Ok(promoted)
+    }
+}
@@ -1,26 +1,91 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Duration;
 use sqlx::PgPool;
 
-use crate::{apple::ApnClient, wallet::PassMaker};
+use crate::wallet::PassMaker;
 mod apple;
+mod barcode;
+mod certificates;
 mod config;
+mod coupon;
+mod customer_view;
+mod device_logs;
+mod email;
+mod event_ticket;
+mod gift_card;
 mod loyality_pass;
+mod membership_tiers;
 mod pass;
+mod pass_auth;
+mod pass_types;
+mod recovery;
+mod scans;
 
-pub use config::AppConfig;
+pub use barcode::{BarcodeSigner, ResolvedBarcode};
+pub use config::{
+    AppConfig, EmailTemplateConfig, EmailTransportKind, MembershipTierConfig, PassTypeConfig,
+    RateLimitStoreKind,
+};
+pub use coupon::NewCoupon;
+pub use customer_view::{CustomerHistoryEntry, CustomerView, CustomerViewLinks};
+pub use email::{EmailDelivery, EmailTemplates, PassEmail};
+pub use event_ticket::{IssuedEventTicket, NewEvent, NewEventTicket, MAX_TICKETS_PER_ISSUANCE};
+pub use gift_card::NewGiftCard;
+pub use membership_tiers::MembershipTiers;
+pub use pass_types::{CertificateInfo, PassType, PassTypeRegistry};
+pub use recovery::PassRecoveryOptions;
+pub use scans::{Scan, ScanAction};
 
 #[derive(Debug)]
 pub struct App {
     pass_maker: PassMaker,
     db_pool: PgPool,
-    apn_client: ApnClient,
+    /// Replaced as a whole when the certificates are reloaded.
+    pass_types: RwLock<Arc<PassTypeRegistry>>,
+    membership_tiers: MembershipTiers,
+    /// Signs the barcodes, if configured.
+    barcode_signer: Option<BarcodeSigner>,
+    /// Builds the links to the customer view, if configured.
+    customer_view_links: Option<CustomerViewLinks>,
+    /// Sends passes by email, if configured.
+    email_delivery: Option<EmailDelivery>,
+    /// How long a replaced auth token is still accepted.
+    auth_token_grace_period: Duration,
 }
 
 impl App {
-    pub fn new(pass_maker: PassMaker, db_pool: PgPool, apn_client: ApnClient) -> Self {
+    pub fn new(
+        pass_maker: PassMaker,
+        db_pool: PgPool,
+        pass_types: PassTypeRegistry,
+        membership_tiers: MembershipTiers,
+        barcode_signer: Option<BarcodeSigner>,
+        customer_view_links: Option<CustomerViewLinks>,
+        auth_token_grace_period: Duration,
+    ) -> Self {
         Self {
             pass_maker,
             db_pool,
-            apn_client,
+            pass_types: RwLock::new(Arc::new(pass_types)),
+            membership_tiers,
+            barcode_signer,
+            customer_view_links,
+            email_delivery: None,
+            auth_token_grace_period,
         }
     }
+
+    /// Enables sending passes by email.
+    pub fn with_email_delivery(mut self, email_delivery: EmailDelivery) -> Self {
+        self.email_delivery = Some(email_delivery);
+        self
+    }
+}
+
+impl App {
+    /// The currently loaded pass types.
+    pub fn pass_types(&self) -> Arc<PassTypeRegistry> {
+        self.pass_types.read().unwrap().clone()
+    }
 }
@@ -6,98 +6,303 @@ use tracing::info;
 use crate::{
     db::{
         queries::{push_tokens_from_serial_number, remove_devices_with_push_tokens},
-        DbPass, DbPassTypeHelper, DbPassTypeLoyality,
+        repository, DbEvent, DbPass, DbPassAuthToken, DbPassType, DbPassTypeHelper,
+        DbPassTypeLoyality,
     },
+    member_code, token,
+    wallet::{PassBarcode, PassIdentity, PassKind},
     Error, Result,
 };
 
-use super::App;
+use super::{App, PassEmail, PassType};
+
+/// The kind of passes with data of `type`.
+pub(super) fn pass_kind(r#type: &DbPassTypeHelper) -> PassKind {
+    match r#type {
+        DbPassTypeHelper::Loyality => PassKind::Loyality,
+        DbPassTypeHelper::Coupon => PassKind::Coupon,
+        DbPassTypeHelper::GiftCard => PassKind::GiftCard,
+        DbPassTypeHelper::EventTicket => PassKind::EventTicket,
+    }
+}
 
 impl App {
-    pub async fn add_pass(&self, pass_holder_name: &str) -> Result<(Package, String)> {
-        let now = chrono::Utc::now();
+    /// Creates a loyality pass. With `email`, the pass is also sent to the customer by email.
+    pub async fn add_pass(
+        &self,
+        pass_holder_name: &str,
+        email: Option<PassEmail>,
+    ) -> Result<(Package, String)> {
+        if let Some(email) = &email {
+            self.check_pass_email(email)?;
+        }
 
         let serial_number = uuid::Uuid::now_v7().to_string();
-        let auth_token = uuid::Uuid::now_v7().to_string();
 
-        // TODO: Should be in same transaction!
-        let pass = DbPass {
-            serial_number: serial_number.clone(),
-            pass_type_id: self.pass_maker.pass_type_identifier().to_string(),
-            auth_token: auth_token.clone(),
-            created_at: now.naive_utc(),
-            last_updated_at: now.naive_utc(),
-            r#type: DbPassTypeHelper::Loyality,
-        };
-
-        pass.insert(&self.db_pool).await?;
-
-        let dbtl = DbPassTypeLoyality {
+        let pass_type = DbPassType::Loyality(DbPassTypeLoyality {
             serial_number: serial_number.clone(),
             total_points: 10,
             current_points: 0,
             already_redeemed: 0,
             pass_holder_name: pass_holder_name.to_string(),
             last_used_at: None,
-        };
+            pass_holder_email: email.as_ref().map(|e| e.to.trim().to_string()),
+            pass_holder_phone: None,
+            lifetime_points: 0,
+            tier: None,
+            tier_reached_at: None,
+        });
 
-        dbtl.insert(&self.db_pool).await?;
-
-        let wallet_pass = self.pass_maker.new_loyality_pass(
-            serial_number.clone(),
-            auth_token,
-            crate::wallet::LoyalityPass {
-                already_redeemed: dbtl.already_redeemed,
-                total_points: dbtl.total_points,
-                current_points: dbtl.current_points,
-                pass_holder_name: dbtl.pass_holder_name,
-                last_use: None,
-            },
-        )?;
+        let (wallet_pass, _) = self
+            .insert_pass(PassKind::Loyality, pass_type, email.as_ref())
+            .await?;
 
         info!("added new pass!");
 
         Ok((wallet_pass, serial_number))
     }
 
-    pub async fn pass_package(&self, pass_serial_number: &str) -> Result<(Package, NaiveDateTime)> {
+    /// Stores a new pass of `kind` with its type specific data and renders it with a new auth
+    /// token. The serial number is taken from the type specific data. With `email`, an email with
+    /// the pass is queued together with the pass.
+    pub(super) async fn insert_pass(
+        &self,
+        kind: PassKind,
+        pass_type: DbPassType,
+        email: Option<&PassEmail>,
+    ) -> Result<(Package, DbPass)> {
+        let now = chrono::Utc::now();
+        let auth_token = token::generate()?;
+
+        let pass_types = self.pass_types();
+        let pass_type_config = pass_types.for_kind(kind)?;
+
+        let pass = DbPass {
+            serial_number: pass_type.serial_number().to_string(),
+            pass_type_id: pass_type_config.pass_type_identifier.clone(),
+            created_at: now.naive_utc(),
+            last_updated_at: now.naive_utc(),
+            r#type: (&pass_type).into(),
+            member_code: member_code::generate()?,
+        };
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        repository::insert_pass(&pass, &pass_type, &mut transaction).await?;
+
+        DbPassAuthToken {
+            token_hash: token::hash(&auth_token),
+            pass_serial_number: pass.serial_number.clone(),
+            created_at: now.naive_utc(),
+            needs_rotation: false,
+            expires_at: None,
+            replacement_key: None,
+        }
+        .insert(&mut *transaction)
+        .await?;
+
+        let customer_view_url = self
+            .create_customer_view_link(&pass.serial_number, now.naive_utc(), &mut *transaction)
+            .await?;
+
+        let pass_holder_name = pass_type.pass_holder_name().unwrap_or_default().to_string();
+
+        let wallet_pass = self
+            .render_pass(
+                pass_type_config,
+                &pass,
+                auth_token,
+                customer_view_url.clone(),
+                pass_type,
+            )
+            .await?;
+
+        if let Some(email) = email {
+            self.queue_pass_email(
+                &pass,
+                &pass_holder_name,
+                customer_view_url.as_deref(),
+                email,
+                &mut *transaction,
+            )
+            .await?;
+        }
+
+        // Only keep the pass if it could be rendered, the customer would not get it otherwise.
+        transaction.commit().await?;
+
+        Ok((wallet_pass, pass))
+    }
+
+    /// Returns the serial number of the pass referenced by its serial number or by its member
+    /// code, as the customer reads it from the back of the pass.
+    pub async fn resolve_pass_reference(&self, reference: &str) -> Result<String> {
+        if uuid::Uuid::try_parse(reference).is_ok() {
+            return Ok(reference.to_string());
+        }
+
+        let member_code = member_code::normalize(reference).ok_or(Error::PassNotFound)?;
+
+        DbPass::serial_number_from_member_code(&member_code, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)
+    }
+
+    /// Renders the pass with `auth_token` as the token for the web service.
+    pub async fn pass_package(
+        &self,
+        pass_serial_number: &str,
+        auth_token: &str,
+    ) -> Result<(Package, NaiveDateTime)> {
         let db_pass = DbPass::from_serial_number_optional(pass_serial_number, &self.db_pool)
             .await?
             .ok_or(Error::PassNotFound)?;
 
+        let pass_types = self.pass_types();
+        let pass_type_config = pass_types.get_configured(&db_pass.pass_type_id)?;
+
         let pass_type = db_pass
             .r#type
             .from_serial_number(pass_serial_number, &self.db_pool)
             .await?;
 
-        let wallet_pass = match pass_type {
-            crate::db::DbPassType::Loyality(l) => self.pass_maker.new_loyality_pass(
-                pass_serial_number.into(),
-                db_pass.auth_token,
+        let customer_view_url = self.customer_view_url(pass_serial_number).await?;
+
+        let wallet_pass = self
+            .render_pass(
+                pass_type_config,
+                &db_pass,
+                auth_token.to_string(),
+                customer_view_url,
+                pass_type,
+            )
+            .await?;
+
+        Ok((wallet_pass, db_pass.last_updated_at))
+    }
+
+    async fn render_pass(
+        &self,
+        pass_type_config: &PassType,
+        pass: &DbPass,
+        auth_token: String,
+        customer_view_url: Option<String>,
+        pass_type: DbPassType,
+    ) -> Result<Package> {
+        let pass_type_identifier = &pass_type_config.pass_type_identifier;
+        let sign_config = &pass_type_config.sign_config;
+        let identity = PassIdentity {
+            serial_number: pass.serial_number.clone(),
+            authentication_token: auth_token,
+            member_code: pass.member_code.clone(),
+            barcode: PassBarcode {
+                kind: pass_type_config.barcode_format,
+                // The member code under a signed barcode could be typed in instead of scanning
+                // it, which would bypass the signature.
+                message: match &self.barcode_signer {
+                    Some(barcode_signer) => barcode_signer.sign(pass)?,
+                    None => pass.member_code.clone(),
+                },
+                alt_text: self
+                    .barcode_signer
+                    .is_none()
+                    .then(|| member_code::display(&pass.member_code)),
+            },
+            customer_view_url,
+        };
+
+        match pass_type {
+            DbPassType::Loyality(l) => self.pass_maker.new_loyality_pass(
+                pass_type_identifier,
+                sign_config,
+                identity,
                 crate::wallet::LoyalityPass {
                     already_redeemed: l.already_redeemed,
                     total_points: l.total_points,
                     current_points: l.current_points,
                     pass_holder_name: l.pass_holder_name,
                     last_use: l.last_used_at.map(|t| Utc.from_utc_datetime(&t)),
+                    // Tiers that were removed from the configuration are no longer shown.
+                    tier: l
+                        .tier
+                        .as_deref()
+                        .and_then(|id| self.membership_tiers.get(id))
+                        .map(|tier| crate::wallet::LoyalityTier {
+                            label: tier.label.clone(),
+                            background_color: tier.background_color,
+                            foreground_color: tier.foreground_color.unwrap_or([255, 255, 255]),
+                            label_color: tier.label_color.unwrap_or([255, 255, 255]),
+                        }),
+                    tiers_configured: !self.membership_tiers.is_empty(),
                 },
-            )?,
-            // _ => return Err(Error::Other("not implemented".into())),
-        };
+            ),
+            DbPassType::Coupon(c) => self.pass_maker.new_coupon_pass(
+                pass_type_identifier,
+                sign_config,
+                identity,
+                crate::wallet::CouponPass {
+                    discount_text: c.discount_text,
+                    details: c.details,
+                    valid_from: c.valid_from.map(|t| Utc.from_utc_datetime(&t)),
+                    valid_until: c.valid_until.map(|t| Utc.from_utc_datetime(&t)),
+                    max_redemptions: c.max_redemptions,
+                    redemptions: c.redemptions,
+                },
+            ),
+            DbPassType::GiftCard(g) => self.pass_maker.new_gift_card_pass(
+                pass_type_identifier,
+                sign_config,
+                identity,
+                crate::wallet::GiftCardPass {
+                    balance: g.balance,
+                    currency: g.currency,
+                    pass_holder_name: g.pass_holder_name,
+                    last_use: g.last_used_at.map(|t| Utc.from_utc_datetime(&t)),
+                },
+            ),
+            DbPassType::EventTicket(e) => {
+                let event = DbEvent::from_id(&e.event_id, &self.db_pool)
+                    .await?
+                    .ok_or(Error::EventNotFound)?;
 
-        Ok((wallet_pass, db_pass.last_updated_at))
+                self.pass_maker.new_event_ticket_pass(
+                    pass_type_identifier,
+                    sign_config,
+                    identity,
+                    crate::wallet::EventTicketPass {
+                        event_id: event.id,
+                        event_name: event.name,
+                        venue: event.venue,
+                        starts_at: Utc.from_utc_datetime(&event.starts_at),
+                        ends_at: event.ends_at.map(|t| Utc.from_utc_datetime(&t)),
+                        attendee_name: e.attendee_name,
+                        seat: e.seat,
+                        checked_in_at: e.checked_in_at.map(|t| Utc.from_utc_datetime(&t)),
+                    },
+                )
+            }
+        }
     }
 
     pub(super) async fn send_update_pass_notification(
         &self,
         pass_serial_number: &str,
     ) -> Result<()> {
+        let db_pass = DbPass::from_serial_number_optional(pass_serial_number, &self.db_pool)
+            .await?
+            .ok_or(Error::PassNotFound)?;
+        let apn_client = self
+            .pass_types()
+            .get_configured(&db_pass.pass_type_id)?
+            .apn_client
+            .clone();
+
         let push_tokens = push_tokens_from_serial_number(pass_serial_number, &self.db_pool).await?;
 
         let notification_results = join_all(
             push_tokens
                 .iter()
-                .map(|push_token| self.apn_client.send_update_pass_notification(push_token)),
+                .map(|push_token| apn_client.send_update_pass_notification(push_token)),
         )
         .await;
 
@@ -0,0 +1,209 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use futures::future::join_all;
+use passes::Package;
+use tracing::{error, info};
+
+use crate::{
+    db::{DbPass, DbPassAuthToken},
+    token, Error, Result,
+};
+
+use super::App;
+
+/// How often expired tokens are removed and old tokens are rotated.
+const AUTH_TOKEN_MAINTENANCE_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+
+impl App {
+    /// Returns the stored token of the pass matching `auth_token`, if there is one and the pass
+    /// is of type `pass_type_id`. Tokens that were replaced are accepted until their grace period
+    /// is over.
+    pub async fn authenticate_pass(
+        &self,
+        pass_type_id: &str,
+        pass_serial_number: &str,
+        auth_token: &str,
+    ) -> Result<Option<DbPassAuthToken>> {
+        if !self.pass_types().contains(pass_type_id) {
+            return Ok(None);
+        }
+
+        let tokens = DbPassAuthToken::valid_from_pass(
+            pass_type_id,
+            pass_serial_number,
+            Utc::now().naive_utc(),
+            &self.db_pool,
+        )
+        .await?;
+
+        Ok(tokens
+            .into_iter()
+            .find(|t| token::verify(auth_token, &t.token_hash)))
+    }
+
+    /// Renders the pass for a device that authenticated with `auth_token`. If the token has to be
+    /// rotated, a new one is embedded into the pass instead and the old one expires after the
+    /// grace period, so other devices with the same pass can still fetch the new token. The new
+    /// token is derived from the old one, so repeated fetches don't add more tokens.
+    pub async fn apple_pass_package(
+        &self,
+        pass_type_id: &str,
+        pass_serial_number: &str,
+        auth_token: &str,
+    ) -> Result<(Package, NaiveDateTime)> {
+        let stored_token = self
+            .authenticate_pass(pass_type_id, pass_serial_number, auth_token)
+            .await?
+            .ok_or(Error::PassNotFound)?;
+
+        if !stored_token.needs_rotation {
+            return self.pass_package(pass_serial_number, auth_token).await;
+        }
+
+        let now = Utc::now().naive_utc();
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        let replacement_key = DbPassAuthToken::retire(
+            &stored_token.token_hash,
+            now + self.auth_token_grace_period,
+            &token::generate()?,
+            &mut *transaction,
+        )
+        .await?;
+        let new_auth_token = token::derive(&replacement_key, auth_token)?;
+
+        DbPassAuthToken {
+            token_hash: token::hash(&new_auth_token),
+            pass_serial_number: pass_serial_number.to_string(),
+            created_at: now,
+            needs_rotation: false,
+            expires_at: None,
+            replacement_key: None,
+        }
+        .insert_if_missing(&mut *transaction)
+        .await?;
+
+        let package = self
+            .pass_package(pass_serial_number, &new_auth_token)
+            .await?;
+
+        transaction.commit().await?;
+
+        info!(serial_number = pass_serial_number, "rotated auth token");
+
+        Ok(package)
+    }
+
+    /// Requests new auth tokens for all devices of the pass and revokes the customer view links
+    /// printed on it. The devices get notified and receive their new token and link with the next
+    /// fetch of the pass.
+    pub async fn rotate_pass_auth_tokens(&self, pass_serial_number: &str) -> Result<()> {
+        if !DbPass::exists(pass_serial_number, &self.db_pool).await? {
+            return Err(Error::PassNotFound);
+        }
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        let count =
+            DbPassAuthToken::request_rotation(pass_serial_number, &mut *transaction).await?;
+        self.revoke_customer_view_links(pass_serial_number, &mut *transaction)
+            .await?;
+        DbPass::touch(
+            &[pass_serial_number.to_string()],
+            Utc::now().naive_utc(),
+            &mut *transaction,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        info!(
+            serial_number = pass_serial_number,
+            count = count,
+            "requested auth token rotation"
+        );
+
+        self.send_update_pass_notification(pass_serial_number).await
+    }
+
+    /// Periodically deletes expired auth tokens and, if `rotation_interval` is set, rotates all
+    /// tokens that are older than it. Never returns.
+    pub async fn run_auth_token_maintenance(&self, rotation_interval: Option<Duration>) {
+        let mut interval = tokio::time::interval(AUTH_TOKEN_MAINTENANCE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.auth_token_maintenance(rotation_interval).await {
+                error!("auth token maintenance failed: {}", err);
+            }
+        }
+    }
+
+    async fn auth_token_maintenance(&self, rotation_interval: Option<Duration>) -> Result<()> {
+        let now = Utc::now().naive_utc();
+
+        let deleted = DbPassAuthToken::delete_expired(now, &self.db_pool).await?;
+        if deleted > 0 {
+            info!(count = deleted, "deleted expired auth tokens");
+        }
+
+        let Some(rotation_interval) = rotation_interval else {
+            return Ok(());
+        };
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        let serial_numbers = DbPassAuthToken::request_rotation_created_before(
+            now - rotation_interval,
+            &mut *transaction,
+        )
+        .await?;
+        DbPass::touch(&serial_numbers, now, &mut *transaction).await?;
+
+        transaction.commit().await?;
+
+        if serial_numbers.is_empty() {
+            return Ok(());
+        }
+
+        info!(
+            count = serial_numbers.len(),
+            "requested scheduled auth token rotation"
+        );
+
+        join_all(
+            serial_numbers
+                .iter()
+                .map(|serial_number| self.send_update_pass_notification(serial_number)),
+        )
+        .await
+        .into_iter()
+        .filter_map(|r| r.err())
+        .for_each(|err| error!("sending update notification failed: {}", err));
+
+        Ok(())
+    }
+
+    pub(super) async fn insert_auth_token(
+        &self,
+        pass_serial_number: &str,
+        auth_token: &str,
+        conn: &mut sqlx::PgConnection,
+    ) -> Result<()> {
+        DbPassAuthToken {
+            token_hash: token::hash(auth_token),
+            pass_serial_number: pass_serial_number.to_string(),
+            created_at: Utc::now().naive_utc(),
+            needs_rotation: false,
+            expires_at: None,
+            replacement_key: None,
+        }
+        .insert(conn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use std::{fs, time::SystemTime};
+
+use chrono::{DateTime, TimeZone, Utc};
+use openssl::{asn1::Asn1Time, pkcs12::Pkcs12, x509::X509};
+
+use crate::{
+    apple::ApnClient,
+    wallet::{BarcodeKind, ISignConfig, PassKind},
+    Error, Result,
+};
+
+use super::PassTypeConfig;
+
+/// Validity of one of the certificates of a pass type.
+#[derive(Debug, Clone)]
+pub struct CertificateInfo {
+    /// Which certificate it is, `signing`, `wwdr` or `apn`.
+    pub name: &'static str,
+    pub path: String,
+    pub not_after: DateTime<Utc>,
+}
+
+impl CertificateInfo {
+    fn new(name: &'static str, path: &str, cert: &X509) -> Result<Self> {
+        let valid_for = Asn1Time::from_unix(0)?.diff(cert.not_after())?;
+        let not_after = Utc
+            .timestamp_opt(
+                i64::from(valid_for.days) * 24 * 60 * 60 + i64::from(valid_for.secs),
+                0,
+            )
+            .single()
+            .ok_or_else(|| Error::Other(format!("invalid expiry of certificate {path}")))?;
+
+        Ok(Self {
+            name,
+            path: path.to_string(),
+            not_after,
+        })
+    }
+}
+
+/// A pass type identifier with the certificates to sign its passes and to notify devices about
+/// updates of them.
+#[derive(Debug)]
+pub struct PassType {
+    pub kind: PassKind,
+    pub pass_type_identifier: String,
+    pub sign_config: ISignConfig,
+    pub apn_client: ApnClient,
+    pub certificates: Vec<CertificateInfo>,
+    pub barcode_format: BarcodeKind,
+}
+
+impl PassType {
+    pub fn load(config: &PassTypeConfig) -> Result<Self> {
+        let sign_config = ISignConfig::new(
+            &config.signing_cert_path,
+            &config.signing_key_path,
+            &config.signing_key_token,
+            config.wwdr_cert_path.as_deref(),
+        )?;
+
+        let mut certificates = vec![CertificateInfo::new(
+            "signing",
+            &config.signing_cert_path,
+            &X509::from_pem(&sign_config.sign_cert)?,
+        )?];
+
+        if let (Some(path), Some(wwdr_cert)) = (&config.wwdr_cert_path, &sign_config.wwdr_cert) {
+            certificates.push(CertificateInfo::new(
+                "wwdr",
+                path,
+                &X509::from_pem(wwdr_cert)?,
+            )?);
+        }
+
+        let apn_cert = Pkcs12::from_der(&fs::read(&config.apn_signing_cert_p12_path)?)?
+            .parse2(&config.apn_signing_cert_p12_token)?
+            .cert
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "no certificate in {}",
+                    config.apn_signing_cert_p12_path
+                ))
+            })?;
+        certificates.push(CertificateInfo::new(
+            "apn",
+            &config.apn_signing_cert_p12_path,
+            &apn_cert,
+        )?);
+
+        Ok(Self {
+            kind: config.kind,
+            pass_type_identifier: config.pass_type_id.clone(),
+            sign_config,
+            apn_client: ApnClient::new(
+                &config.apn_signing_cert_p12_path,
+                &config.apn_signing_cert_p12_token,
+                config.pass_type_id.clone(),
+            )?,
+            certificates,
+            barcode_format: config.barcode_format,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PassTypeRegistry {
+    configs: Vec<PassTypeConfig>,
+    /// In order of configuration, the first pass type of a kind is used for new passes.
+    pass_types: Vec<PassType>,
+}
+
+impl PassTypeRegistry {
+    /// Reads the certificates of all pass types.
+    pub fn load(configs: Vec<PassTypeConfig>) -> Result<Self> {
+        for (i, config) in configs.iter().enumerate() {
+            if configs[..i]
+                .iter()
+                .any(|other| other.pass_type_id == config.pass_type_id)
+            {
+                return Err(Error::Other(format!(
+                    "pass type {} is configured more than once",
+                    config.pass_type_id
+                )));
+            }
+        }
+
+        let pass_types = configs.iter().map(PassType::load).collect::<Result<_>>()?;
+
+        Ok(Self {
+            configs,
+            pass_types,
+        })
+    }
+
+    /// Reads the certificates again, e.g. after they were renewed.
+    pub fn reload(&self) -> Result<Self> {
+        Self::load(self.configs.clone())
+    }
+
+    /// Modification times of all certificate and key files, to notice when they are replaced.
+    pub fn file_modification_times(&self) -> Vec<Option<SystemTime>> {
+        self.configs
+            .iter()
+            .flat_map(|config| {
+                [
+                    Some(&config.signing_cert_path),
+                    Some(&config.signing_key_path),
+                    config.wwdr_cert_path.as_ref(),
+                    Some(&config.apn_signing_cert_p12_path),
+                ]
+            })
+            .flatten()
+            .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PassType> {
+        self.pass_types.iter()
+    }
+
+    pub fn get(&self, pass_type_identifier: &str) -> Option<&PassType> {
+        self.pass_types
+            .iter()
+            .find(|pass_type| pass_type.pass_type_identifier == pass_type_identifier)
+    }
+
+    /// Like [`Self::get`], for passes that must have a configured pass type.
+    pub fn get_configured(&self, pass_type_identifier: &str) -> Result<&PassType> {
+        self.get(pass_type_identifier)
+            .ok_or_else(|| Error::PassTypeNotConfigured(pass_type_identifier.to_string()))
+    }
+
+    pub fn contains(&self, pass_type_identifier: &str) -> bool {
+        self.get(pass_type_identifier).is_some()
+    }
+
+    /// The pass type new passes of `kind` are issued with, the first configured one. For loyality
+    /// passes that is the pass type configured by the settings.
+    pub fn for_kind(&self, kind: PassKind) -> Result<&PassType> {
+        self.pass_types
+            .iter()
+            .find(|pass_type| pass_type.kind == kind)
+            .ok_or_else(|| Error::Other(format!("no pass type configured for {kind:?} passes")))
+    }
+}
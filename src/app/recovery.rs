@@ -0,0 +1,125 @@
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use passes::Package;
+use tracing::info;
+
+use crate::{
+    db::{DbDevice, DbPass, DbPassAuthToken, DbPassRecoveryToken},
+    token, Error, Result,
+};
+
+use super::App;
+
+/// How long a recovery link can be used to download the pass.
+const RECOVERY_LINK_VALIDITY: Duration = Duration::hours(72);
+
+pub struct PassRecoveryOptions {
+    /// Revokes all auth tokens and customer view links of the pass, so the old devices can no
+    /// longer fetch updates and the link on the old pass stops working.
+    pub rotate_auth_token: bool,
+    /// Removes all device registrations of the pass, so no more updates are pushed to them.
+    pub invalidate_registrations: bool,
+}
+
+impl App {
+    /// Creates a token with which the pass can be downloaded once more without authentication,
+    /// e.g. to move it to a new phone. Returns the token and its expiry.
+    pub async fn create_pass_recovery(
+        &self,
+        pass_serial_number: &str,
+        options: PassRecoveryOptions,
+        created_by: &str,
+    ) -> Result<(String, DateTime<Utc>)> {
+        if !DbPass::exists(pass_serial_number, &self.db_pool).await? {
+            return Err(Error::PassNotFound);
+        }
+
+        let now = Utc::now();
+        let (recovery_token, db_recovery_token) =
+            new_recovery_token(pass_serial_number, created_by, now)?;
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        db_recovery_token.insert(&mut *transaction).await?;
+
+        if options.rotate_auth_token {
+            DbPassAuthToken::delete_for_pass(pass_serial_number, &mut *transaction).await?;
+            self.revoke_customer_view_links(pass_serial_number, &mut *transaction)
+                .await?;
+        }
+
+        if options.invalidate_registrations {
+            let removed =
+                DbDevice::remove_all_for_pass(pass_serial_number, &mut transaction).await?;
+            info!(
+                serial_number = pass_serial_number,
+                count = removed,
+                "removed device registrations for pass recovery"
+            );
+        }
+
+        transaction.commit().await?;
+
+        info!(
+            serial_number = pass_serial_number,
+            created_by = created_by,
+            rotate_auth_token = options.rotate_auth_token,
+            "created pass recovery"
+        );
+
+        Ok((recovery_token, now + RECOVERY_LINK_VALIDITY))
+    }
+
+    /// Downloads the pass with a recovery token. The token is used up, so a leaked link can't
+    /// be used again.
+    pub async fn recover_pass(&self, recovery_token: &str) -> Result<(Package, NaiveDateTime)> {
+        let auth_token = token::generate()?;
+
+        let mut transaction = self.db_pool.begin().await?;
+
+        let recovery = DbPassRecoveryToken::consume(
+            &token::hash(recovery_token),
+            Utc::now().naive_utc(),
+            &mut *transaction,
+        )
+        .await?
+        .ok_or(Error::RecoveryTokenInvalid)?;
+
+        self.insert_auth_token(&recovery.pass_serial_number, &auth_token, &mut transaction)
+            .await?;
+
+        let package = self
+            .pass_package(&recovery.pass_serial_number, &auth_token)
+            .await?;
+
+        // The token is only used up if the pass could be rendered.
+        transaction.commit().await?;
+
+        info!(
+            serial_number = recovery.pass_serial_number,
+            created_at = %Utc.from_utc_datetime(&recovery.created_at),
+            "pass recovered"
+        );
+
+        Ok(package)
+    }
+}
+
+/// Generates a recovery token for the pass. Returns the token and its row, which the caller has
+/// to insert.
+fn new_recovery_token(
+    pass_serial_number: &str,
+    created_by: &str,
+    now: DateTime<Utc>,
+) -> Result<(String, DbPassRecoveryToken)> {
+    let recovery_token = token::generate()?;
+
+    let db_recovery_token = DbPassRecoveryToken {
+        token_hash: token::hash(&recovery_token),
+        pass_serial_number: pass_serial_number.to_string(),
+        created_at: now.naive_utc(),
+        expires_at: (now + RECOVERY_LINK_VALIDITY).naive_utc(),
+        created_by: created_by.to_string(),
+    };
+
+    Ok((recovery_token, db_recovery_token))
+}
@@ -0,0 +1,99 @@
+use chrono::Utc;
+use tracing::info;
+
+use crate::{
+    db::{DbPassType, DbScanEvent},
+    Result,
+};
+
+use super::{App, ResolvedBarcode};
+
+/// What the staff can do with a scanned pass in its current state.
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanAction {
+    AddPoints,
+    RedeemBonus,
+    RedeemCoupon,
+    TopUpGiftCard,
+    SpendGiftCard,
+    CheckInTicket,
+}
+
+pub struct Scan {
+    pub event: DbScanEvent,
+    pub barcode: ResolvedBarcode,
+    pub pass_type: DbPassType,
+    pub actions: Vec<ScanAction>,
+}
+
+fn allowed_actions(pass_type: &DbPassType) -> Vec<ScanAction> {
+    let now = Utc::now().naive_utc();
+
+    match pass_type {
+        DbPassType::Loyality(l) if l.current_points >= l.total_points => {
+            vec![ScanAction::RedeemBonus]
+        }
+        DbPassType::Loyality(_) => vec![ScanAction::AddPoints],
+        DbPassType::Coupon(c)
+            if !c.used_up()
+                && c.valid_from.is_none_or(|valid_from| valid_from <= now)
+                && c.valid_until.is_none_or(|valid_until| valid_until > now) =>
+        {
+            vec![ScanAction::RedeemCoupon]
+        }
+        DbPassType::Coupon(_) => Vec::new(),
+        DbPassType::GiftCard(g) if g.balance > 0 => {
+            vec![ScanAction::TopUpGiftCard, ScanAction::SpendGiftCard]
+        }
+        DbPassType::GiftCard(_) => vec![ScanAction::TopUpGiftCard],
+        DbPassType::EventTicket(e) if e.checked_in_at.is_none() => {
+            vec![ScanAction::CheckInTicket]
+        }
+        DbPassType::EventTicket(_) => Vec::new(),
+    }
+}
+
+impl App {
+    /// Resolves a scanned barcode, records the scan and returns the pass with what can be done
+    /// with it.
+    pub async fn scan_pass(
+        &self,
+        payload: &str,
+        store: Option<&str>,
+        scanned_by: &str,
+    ) -> Result<Scan> {
+        let barcode = self.resolve_barcode(payload).await?;
+
+        let pass_type = barcode
+            .pass
+            .r#type
+            .from_serial_number(&barcode.pass.serial_number, &self.db_pool)
+            .await?;
+
+        let event = DbScanEvent::insert(
+            &barcode.pass.serial_number,
+            Utc::now().naive_utc(),
+            scanned_by,
+            store.map(str::trim).filter(|s| !s.is_empty()),
+            barcode.signed,
+            &self.db_pool,
+        )
+        .await?;
+
+        info!(
+            serial_number = barcode.pass.serial_number,
+            scanned_by = scanned_by,
+            store = event.store,
+            signed = barcode.signed,
+            "pass scanned"
+        );
+
+        Ok(Scan {
+            event,
+            actions: allowed_actions(&pass_type),
+            barcode,
+            pass_type,
+        })
+    }
+}
@@ -4,11 +4,13 @@ use std::fs::File;
 #[derive(Debug, Clone)]
 pub struct ApnClient {
     base_client: std::sync::Arc<Client>,
+    /// The pass type identifier the certificate was issued for.
+    topic: String,
 }
 
 impl ApnClient {
-    pub fn new(certificate_path: &str, password: &str) -> Result<Self, a2::Error> {
-        let mut certificate = File::open(certificate_path).unwrap();
+    pub fn new(certificate_path: &str, password: &str, topic: String) -> Result<Self, a2::Error> {
+        let mut certificate = File::open(certificate_path)?;
 
         Ok(Self {
             base_client: Client::certificate(
@@ -20,6 +22,7 @@ impl ApnClient {
                 },
             )?
             .into(),
+            topic,
         })
     }
 
@@ -29,7 +32,10 @@ impl ApnClient {
     ) -> Result<Response, a2::Error> {
         let builder = DefaultNotificationBuilder::new();
 
-        let options = NotificationOptions::default();
+        let options = NotificationOptions {
+            apns_topic: Some(&self.topic),
+            ..Default::default()
+        };
 
         let payload = builder.build(device_token, options);
 
@@ -3,5 +3,5 @@ mod logs;
 mod registration;
 
 pub use auth::AuthToken;
-// pub use logs::Logs;
+pub use logs::Logs;
 pub use registration::DeviceRegistrationPushToken;
@@ -11,10 +11,13 @@ use crate::{
 
 pub async fn handle_get_pass(
     State(state): State<AppState>,
-    _: AuthToken,
-    Path((_, serial_number)): Path<(String, String)>,
+    AuthToken(auth_token): AuthToken,
+    Path((pass_type_id, serial_number)): Path<(String, String)>,
 ) -> Result<(HeaderMap, Body)> {
-    let (mut wallet_pass, last_updated_at) = state.app.pass_package(&serial_number).await?;
+    let (mut wallet_pass, last_updated_at) = state
+        .app
+        .apple_pass_package(&pass_type_id, &serial_number, &auth_token)
+        .await?;
 
     let last_updated_at_timestamp = Utc
         .from_utc_datetime(&last_updated_at)
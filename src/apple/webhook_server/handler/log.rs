@@ -1,3 +1,9 @@
-pub async fn handle_log(body: String) {
-    tracing::warn!(logs = body, "device sent logs");
+use axum::{extract::State, http::StatusCode};
+
+use crate::{apple::webhook_server::extractors::Logs, http::AppState, Result};
+
+pub async fn handle_log(State(state): State<AppState>, Logs { logs }: Logs) -> Result<StatusCode> {
+    state.app.store_device_logs(&logs).await?;
+
+    Ok(StatusCode::OK)
 }
@@ -4,24 +4,30 @@ use axum::{
     response::Response,
 };
 
-use crate::{
-    apple::webhook_server::extractors::AuthToken, db::queries::correct_serial_number_auth_token,
-    http::AppState, Error,
-};
+use crate::{apple::webhook_server::extractors::AuthToken, http::AppState, Error};
 
 #[derive(serde::Deserialize)]
 pub struct PathParams {
+    pub pass_type_id: String,
     pub serial_number: String,
 }
 
 pub async fn check_pass_auth(
     State(state): State<AppState>,
-    Path(PathParams { serial_number }): Path<PathParams>,
+    Path(PathParams {
+        pass_type_id,
+        serial_number,
+    }): Path<PathParams>,
     AuthToken(auth_token): AuthToken,
     req: Request,
     next: Next,
 ) -> Result<Response, Error> {
-    if !correct_serial_number_auth_token(&serial_number, &auth_token, &state.db_pool).await? {
+    if state
+        .app
+        .authenticate_pass(&pass_type_id, &serial_number, &auth_token)
+        .await?
+        .is_none()
+    {
         return Err(Error::PassNotFound);
     }
 
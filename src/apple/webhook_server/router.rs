@@ -3,18 +3,29 @@ use axum::{
     Router,
 };
 
-use crate::http::AppState;
+use crate::http::{rate_limit, AppState, RateLimitScope};
 
 use super::{handler, middleware::check_pass_auth};
 
 pub fn router(state: AppState) -> Router {
-    Router::new()
+    // The rate limit is the outer layer, so requests with wrong auth tokens count as well.
+    let registrations = Router::new()
         .route(
             "/v1/devices/{device_library_id}/registrations/{pass_type_id}/{serial_number}",
             post(handler::handle_device_registration)
                 .get(handler::handle_device_registration)
                 .delete(handler::handle_device_deregistration),
         )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            check_pass_auth,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            (state.clone(), RateLimitScope::AppleRegistration),
+            rate_limit,
+        ));
+
+    Router::new()
         .route(
             "/v1/passes/{pass_type_id}/{serial_number}",
             get(handler::handle_get_pass),
@@ -23,10 +34,22 @@ pub fn router(state: AppState) -> Router {
             state.clone(),
             check_pass_auth,
         ))
+        .merge(registrations)
         .route(
             "/v1/devices/{device_library_id}/registrations/{pass_type_id}",
-            get(handler::handle_list_updatable_passes),
+            get(handler::handle_list_updatable_passes).route_layer(
+                axum::middleware::from_fn_with_state(
+                    (state.clone(), RateLimitScope::AppleRegistration),
+                    rate_limit,
+                ),
+            ),
+        )
+        .route(
+            "/v1/log",
+            post(handler::handle_log).route_layer(axum::middleware::from_fn_with_state(
+                (state.clone(), RateLimitScope::AppleLog),
+                rate_limit,
+            )),
         )
-        .route("/v1/log", post(handler::handle_log))
         .with_state(state)
 }
@@ -1,41 +1,71 @@
 use std::sync::Arc;
 
 use carte_etoile::{
-    app::{App, AppConfig},
-    apple::ApnClient,
+    app::{
+        App, AppConfig, BarcodeSigner, CustomerViewLinks, MembershipTiers, PassTypeRegistry,
+        RateLimitStoreKind,
+    },
     db,
-    http::{self, InnerAppState, OidcValidator},
+    http::{
+        self, InnerAppState, MemoryRateLimitStore, OidcValidator, PostgresRateLimitStore,
+        RateLimitStore, RateLimiter,
+    },
     image::ImageMaker,
     setup_tracing,
-    wallet::{ISignConfig, PassMaker},
+    wallet::PassMaker,
     Result,
 };
+use chrono::Duration;
 use dotenvy::dotenv;
+use tracing::error;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     setup_tracing();
 
-    let config = AppConfig::from_env()?;
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    };
 
     let db_pool = db::connect(&config.database_url).await?;
 
-    let apn_client = ApnClient::new(
-        &config.apn_signing_cert_p12_path,
-        &config.apn_signing_cert_p12_token,
-    )?;
+    db::check_consistency(&db_pool, config.db_repair_orphaned_passes).await?;
 
-    let sign_config = ISignConfig::new(
-        &config.pass_signing_cert_path,
-        &config.pass_signing_key_path,
-        &config.pass_signing_key_token,
-    )?;
+    let rate_limit_store = match config.rate_limit_store {
+        RateLimitStoreKind::Memory => RateLimitStore::Memory(MemoryRateLimitStore::default()),
+        RateLimitStoreKind::Postgres => {
+            RateLimitStore::Postgres(PostgresRateLimitStore::new(db_pool.clone()))
+        }
+    };
+    let rate_limiter = RateLimiter::new(
+        rate_limit_store,
+        config.rate_limit_rules(),
+        config.rate_limit_trust_forwarded_for,
+    );
+
+    let pass_types = PassTypeRegistry::load(config.pass_types()?)?;
+    let membership_tiers = MembershipTiers::new(config.membership_tiers()?)?;
+    let barcode_signer = config
+        .pass_barcode_secret
+        .as_deref()
+        .map(BarcodeSigner::new)
+        .transpose()?;
+    let email_delivery = config.email_delivery()?;
+    let customer_view_links = match (
+        config.pass_customer_view_secret.as_deref(),
+        config.http_public_url.as_deref(),
+    ) {
+        (Some(secret), Some(public_url)) => Some(CustomerViewLinks::new(secret, public_url)?),
+        _ => None,
+    };
 
     let pass_maker = PassMaker::new(
-        sign_config,
         config.pass_team_identifier,
-        config.pass_type_id,
         config.pass_web_service_url,
         config.pass_logo_path,
         config.pass_icon_path,
@@ -48,13 +78,52 @@ async fn main() -> Result<()> {
 
     let oidc_validator = OidcValidator::new(config.oidc_url).await?;
 
-    let app = App::new(pass_maker, db_pool.clone(), apn_client.clone());
+    let app = App::new(
+        pass_maker,
+        db_pool.clone(),
+        pass_types,
+        membership_tiers,
+        barcode_signer,
+        customer_view_links,
+        Duration::hours(config.pass_auth_token_grace_period_hours.into()),
+    );
+    let app = match email_delivery {
+        Some(email_delivery) => app.with_email_delivery(email_delivery),
+        None => app,
+    };
 
     let state = Arc::new(InnerAppState {
         app,
-        apn_client,
         db_pool,
         oidc_validator,
+        rate_limiter,
+        public_url: config.http_public_url,
+    });
+
+    let rotation_interval = config
+        .pass_auth_token_rotation_interval_days
+        .map(|days| Duration::days(days.into()));
+    let maintenance_state = state.clone();
+    tokio::spawn(async move {
+        maintenance_state
+            .app
+            .run_auth_token_maintenance(rotation_interval)
+            .await
+    });
+
+    let email_queue_state = state.clone();
+    tokio::spawn(async move { email_queue_state.app.run_email_queue().await });
+
+    let certificate_expiry_warning = Duration::days(config.certificate_expiry_warning_days.into());
+    let certificate_state = state.clone();
+    tokio::spawn(async move {
+        if let Err(err) = certificate_state
+            .app
+            .run_certificate_maintenance(certificate_expiry_warning)
+            .await
+        {
+            error!("certificate maintenance stopped: {}", err);
+        }
     });
 
     http::start(&config.http_listener_host, state).await
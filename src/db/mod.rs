@@ -1,9 +1,10 @@
 mod models;
 pub mod queries;
+pub mod repository;
 
 pub use models::*;
 use sqlx::{postgres::PgPoolOptions, PgPool};
-use tracing::info;
+use tracing::{info, warn};
 
 pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
     info!("Connecting to database...");
@@ -17,3 +18,27 @@ pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
 
     Ok(db_pool)
 }
+
+/// Looks for passes that are missing their type specific data, which can only happen if they were
+/// created before pass creation was transactional. They are reported and, if `repair` is set,
+/// deleted, as such passes can never be rendered.
+pub async fn check_consistency(db_pool: &PgPool, repair: bool) -> Result<(), sqlx::Error> {
+    let orphaned = repository::orphaned_passes(db_pool).await?;
+
+    if orphaned.is_empty() {
+        return Ok(());
+    }
+
+    warn!(
+        count = orphaned.len(),
+        serial_numbers = ?orphaned,
+        "found passes without type specific data"
+    );
+
+    if repair {
+        let deleted = repository::delete_passes(&orphaned, db_pool).await?;
+        info!(count = deleted, "deleted orphaned passes");
+    }
+
+    Ok(())
+}
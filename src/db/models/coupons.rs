@@ -0,0 +1,108 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+#[derive(FromRow, Debug)]
+pub struct DbPassTypeCoupon {
+    pub serial_number: String,
+    /// The discount as shown on the pass, e.g. `10% off`.
+    pub discount_text: String,
+    pub details: Option<String>,
+    pub valid_from: Option<NaiveDateTime>,
+    pub valid_until: Option<NaiveDateTime>,
+    /// How often the coupon can be redeemed, 1 for single use coupons.
+    pub max_redemptions: i32,
+    pub redemptions: i32,
+    pub last_redeemed_at: Option<NaiveDateTime>,
+}
+
+impl DbPassTypeCoupon {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO pass_type_coupon (serial_number, discount_text, details, valid_from, valid_until, max_redemptions, redemptions, last_redeemed_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            &self.serial_number,
+            &self.discount_text,
+            self.details.as_deref(),
+            self.valid_from,
+            self.valid_until,
+            self.max_redemptions,
+            self.redemptions,
+            self.last_redeemed_at,
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn from_serial_number(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_coupon WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    pub async fn from_serial_number_optional(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_coupon WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Counts a redemption if the coupon is valid at `now` and not used up yet and returns the
+    /// updated row. Returns `None` otherwise.
+    pub async fn redeem(
+        serial_number: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut transaction = conn.begin().await?;
+        let coupon = sqlx::query_as!(
+            Self,
+            "
+UPDATE pass_type_coupon SET redemptions=redemptions+1, last_redeemed_at=$1
+WHERE serial_number=$2
+AND redemptions<max_redemptions
+AND (valid_from IS NULL OR valid_from<=$1)
+AND (valid_until IS NULL OR valid_until>$1)
+RETURNING *
+",
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        if coupon.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(coupon)
+    }
+
+    pub fn used_up(&self) -> bool {
+        self.redemptions >= self.max_redemptions
+    }
+}
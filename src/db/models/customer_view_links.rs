@@ -0,0 +1,79 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor};
+
+/// A link to the customer view of a pass, printed on the back of the pass.
+#[derive(FromRow, Debug)]
+pub struct DbCustomerViewLink {
+    pub token_hash: String,
+    pub pass_serial_number: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+impl DbCustomerViewLink {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO customer_view_links (token_hash, pass_serial_number, created_at, expires_at, revoked_at) VALUES ($1, $2, $3, $4, $5)",
+            &self.token_hash,
+            &self.pass_serial_number,
+            self.created_at,
+            self.expires_at,
+            self.revoked_at,
+        )
+        .execute(conn)
+        .await
+    }
+
+    /// The newest link of the pass that is neither expired nor revoked.
+    pub async fn current<'c>(
+        pass_serial_number: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM customer_view_links WHERE pass_serial_number=$1 AND revoked_at IS NULL AND expires_at>$2 ORDER BY created_at DESC LIMIT 1",
+            pass_serial_number,
+            now
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    pub async fn valid_from_token_hash<'c>(
+        token_hash: &str,
+        pass_serial_number: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM customer_view_links WHERE token_hash=$1 AND pass_serial_number=$2 AND revoked_at IS NULL AND expires_at>$3",
+            token_hash,
+            pass_serial_number,
+            now
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Revokes all links of the pass, returns how many were revoked.
+    pub async fn revoke_for_pass<'c>(
+        pass_serial_number: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query!(
+            "UPDATE customer_view_links SET revoked_at=$2 WHERE pass_serial_number=$1 AND revoked_at IS NULL",
+            pass_serial_number,
+            now
+        )
+        .execute(conn)
+        .await?
+        .rows_affected())
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+#[derive(FromRow, Debug)]
+pub struct DbDeviceLog {
+    /// Hash of the message without its timestamp, repeated messages are counted instead of
+    /// stored again.
+    pub message_hash: String,
+    pub message: String,
+    pub device_library_id: Option<String>,
+    pub pass_type_id: Option<String>,
+    pub pass_serial_number: Option<String>,
+    /// Timestamps reported by the device, if the message contained one.
+    pub first_logged_at: Option<NaiveDateTime>,
+    pub last_logged_at: Option<NaiveDateTime>,
+    /// Timestamps when the message was received.
+    pub first_seen_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub occurrences: i32,
+}
+
+#[derive(Debug, Default)]
+pub struct DbDeviceLogFilter {
+    pub device_library_id: Option<String>,
+    pub pass_serial_number: Option<String>,
+    pub seen_after: Option<NaiveDateTime>,
+}
+
+impl DbDeviceLog {
+    /// Inserts the log entry or, if the same message was already received, counts it as another
+    /// occurrence.
+    pub async fn upsert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO device_logs (message_hash, message, device_library_id, pass_type_id, pass_serial_number, first_logged_at, last_logged_at, first_seen_at, last_seen_at, occurrences)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (message_hash) DO UPDATE SET
+                occurrences=device_logs.occurrences+EXCLUDED.occurrences,
+                first_logged_at=LEAST(device_logs.first_logged_at, EXCLUDED.first_logged_at),
+                last_logged_at=GREATEST(device_logs.last_logged_at, EXCLUDED.last_logged_at),
+                last_seen_at=GREATEST(device_logs.last_seen_at, EXCLUDED.last_seen_at)",
+            &self.message_hash,
+            &self.message,
+            self.device_library_id.as_deref(),
+            self.pass_type_id.as_deref(),
+            self.pass_serial_number.as_deref(),
+            self.first_logged_at,
+            self.last_logged_at,
+            self.first_seen_at,
+            self.last_seen_at,
+            self.occurrences,
+        )
+        .execute(conn)
+        .await
+    }
+
+    /// Returns one page of the matching entries, most recently seen first, and the total amount
+    /// of matching entries.
+    pub async fn search(
+        filter: &DbDeviceLogFilter,
+        limit: i64,
+        offset: i64,
+        conn: &PgPool,
+    ) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        let logs = sqlx::query_as!(
+            Self,
+            "SELECT * FROM device_logs
+            WHERE ($1::TEXT IS NULL OR device_library_id=$1)
+                AND ($2::TEXT IS NULL OR pass_serial_number=$2)
+                AND ($3::TIMESTAMP IS NULL OR last_seen_at>=$3)
+            ORDER BY last_seen_at DESC, message_hash
+            LIMIT $4 OFFSET $5",
+            filter.device_library_id.as_deref(),
+            filter.pass_serial_number.as_deref(),
+            filter.seen_after,
+            limit,
+            offset,
+        )
+        .fetch_all(conn)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM device_logs
+            WHERE ($1::TEXT IS NULL OR device_library_id=$1)
+                AND ($2::TEXT IS NULL OR pass_serial_number=$2)
+                AND ($3::TIMESTAMP IS NULL OR last_seen_at>=$3)"#,
+            filter.device_library_id.as_deref(),
+            filter.pass_serial_number.as_deref(),
+            filter.seen_after,
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok((logs, total))
+    }
+
+    pub async fn delete_last_seen_before<'c>(
+        before: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(
+            sqlx::query!("DELETE FROM device_logs WHERE last_seen_at<$1", before)
+                .execute(conn)
+                .await?
+                .rows_affected(),
+        )
+    }
+}
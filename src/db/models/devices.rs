@@ -1,4 +1,4 @@
-use sqlx::{prelude::FromRow, PgPool};
+use sqlx::{prelude::FromRow, PgConnection, PgPool};
 
 #[derive(FromRow)]
 pub struct DbDevice {
@@ -46,4 +46,27 @@ impl DbDevice {
 
         Ok(())
     }
+
+    /// Removes all registrations of a pass and deletes the devices that have no other passes
+    /// left. Returns the amount of removed registrations.
+    pub async fn remove_all_for_pass(
+        pass_serial_number: &str,
+        conn: &mut PgConnection,
+    ) -> Result<u64, sqlx::Error> {
+        let device_library_ids = sqlx::query_scalar!(
+            "DELETE FROM device_pass_registrations WHERE pass_serial_number=$1 RETURNING device_library_id",
+            pass_serial_number
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM devices d WHERE d.device_library_id=ANY($1) AND NOT EXISTS (SELECT 1 FROM device_pass_registrations dpr WHERE dpr.device_library_id=d.device_library_id)",
+            &device_library_ids
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(device_library_ids.len() as u64)
+    }
 }
@@ -0,0 +1,109 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+/// An email waiting to be sent, or sent already. Failed attempts are retried until the email is
+/// given up.
+#[derive(FromRow, Debug)]
+pub struct DbEmailOutbox {
+    pub id: i64,
+    /// The pass attached to the email, rendered when the email is sent.
+    pub pass_serial_number: Option<String>,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub sent_at: Option<NaiveDateTime>,
+    /// Set once the email is given up.
+    pub failed_at: Option<NaiveDateTime>,
+}
+
+impl DbEmailOutbox {
+    pub async fn insert<'c>(
+        pass_serial_number: Option<&str>,
+        recipient: &str,
+        subject: &str,
+        body: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "INSERT INTO email_outbox (pass_serial_number, recipient, subject, body, next_attempt_at, created_at) VALUES ($1, $2, $3, $4, $5, $5) RETURNING *",
+            pass_serial_number,
+            recipient,
+            subject,
+            body,
+            now,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Returns the emails that are due and moves their next attempt to `lease_until`, so other
+    /// instances don't send them at the same time.
+    pub async fn claim_due(
+        now: NaiveDateTime,
+        lease_until: NaiveDateTime,
+        limit: i64,
+        conn: &PgPool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "UPDATE email_outbox SET next_attempt_at=$2 WHERE id IN (SELECT id FROM email_outbox WHERE sent_at IS NULL AND failed_at IS NULL AND next_attempt_at<=$1 ORDER BY next_attempt_at LIMIT $3 FOR UPDATE SKIP LOCKED) RETURNING *",
+            now,
+            lease_until,
+            limit
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    pub async fn mark_sent<'c>(
+        id: i64,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "UPDATE email_outbox SET attempts=attempts+1, sent_at=$2 WHERE id=$1",
+            id,
+            now
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn reschedule(
+        id: i64,
+        error: &str,
+        next_attempt_at: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "UPDATE email_outbox SET attempts=attempts+1, last_error=$2, next_attempt_at=$3 WHERE id=$1",
+            id,
+            error,
+            next_attempt_at
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn give_up(
+        id: i64,
+        error: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "UPDATE email_outbox SET attempts=attempts+1, last_error=$2, failed_at=$3 WHERE id=$1",
+            id,
+            error,
+            now
+        )
+        .execute(conn)
+        .await
+    }
+}
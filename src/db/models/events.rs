@@ -0,0 +1,142 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+#[derive(FromRow, Debug)]
+pub struct DbEvent {
+    pub id: String,
+    pub name: String,
+    pub venue: Option<String>,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub created_by: String,
+}
+
+impl DbEvent {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO events (id, name, venue, starts_at, ends_at, created_at, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &self.id,
+            &self.name,
+            self.venue.as_deref(),
+            self.starts_at,
+            self.ends_at,
+            self.created_at,
+            &self.created_by,
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn from_id<'c>(
+        id: &str,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(Self, "SELECT * FROM events WHERE id=$1", id)
+            .fetch_optional(conn)
+            .await
+    }
+
+    /// Returns how many tickets were issued for the event and how many of them checked in.
+    pub async fn ticket_counts(id: &str, conn: &PgPool) -> Result<(i64, i64), sqlx::Error> {
+        let counts = sqlx::query!(
+            r#"SELECT COUNT(*) AS "issued!", COUNT(checked_in_at) AS "checked_in!" FROM pass_type_event_ticket WHERE event_id=$1"#,
+            id
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok((counts.issued, counts.checked_in))
+    }
+}
+
+#[derive(FromRow, Debug)]
+pub struct DbPassTypeEventTicket {
+    pub serial_number: String,
+    pub event_id: String,
+    pub attendee_name: Option<String>,
+    /// Seat or time slot of the attendee.
+    pub seat: Option<String>,
+    pub checked_in_at: Option<NaiveDateTime>,
+}
+
+impl DbPassTypeEventTicket {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO pass_type_event_ticket (serial_number, event_id, attendee_name, seat, checked_in_at) VALUES ($1, $2, $3, $4, $5)",
+            &self.serial_number,
+            &self.event_id,
+            self.attendee_name.as_deref(),
+            self.seat.as_deref(),
+            self.checked_in_at,
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn from_serial_number(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_event_ticket WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    pub async fn from_serial_number_optional(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_event_ticket WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Marks the ticket as used, if it was not used before. Returns `None` if the ticket does not
+    /// exist or is already checked in.
+    pub async fn check_in(
+        serial_number: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut transaction = conn.begin().await?;
+        let ticket = sqlx::query_as!(
+            Self,
+            "UPDATE pass_type_event_ticket SET checked_in_at=$1 WHERE serial_number=$2 AND checked_in_at IS NULL RETURNING *",
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        if ticket.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(ticket)
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+#[derive(FromRow, Debug)]
+pub struct DbPassTypeGiftCard {
+    pub serial_number: String,
+    /// In minor currency units, e.g. cents.
+    pub balance: i64,
+    /// ISO 4217 code, e.g. `EUR`.
+    pub currency: String,
+    pub pass_holder_name: Option<String>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// An entry of the ledger of a gift card, positive amounts are top-ups and negative amounts are
+/// spendings.
+#[derive(FromRow, Debug)]
+pub struct DbGiftCardTransaction {
+    pub id: i64,
+    pub pass_serial_number: String,
+    pub amount: i64,
+    pub balance_after: i64,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// The admin who booked the transaction, not set for the initial balance.
+    pub created_by: Option<String>,
+}
+
+impl DbPassTypeGiftCard {
+    /// Inserts the gift card and books its initial balance as first ledger entry.
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "
+WITH gift_card AS (
+    INSERT INTO pass_type_gift_card (serial_number, balance, currency, pass_holder_name, last_used_at)
+    VALUES ($1, $2, $3, $4, $5)
+    RETURNING serial_number, balance
+)
+INSERT INTO gift_card_transactions (pass_serial_number, amount, balance_after, note, created_at)
+SELECT serial_number, balance, balance, 'initial balance', $6 FROM gift_card WHERE balance > 0
+",
+            &self.serial_number,
+            self.balance,
+            &self.currency,
+            self.pass_holder_name.as_deref(),
+            self.last_used_at,
+            chrono::Utc::now().naive_utc(),
+        )
+        .execute(conn)
+        .await
+    }
+
+    pub async fn from_serial_number(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_gift_card WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    pub async fn from_serial_number_optional(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM pass_type_gift_card WHERE serial_number=$1",
+            serial_number
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Adds `amount` to the balance and books it in the ledger, if the balance does not get
+    /// negative. Returns `None` if the gift card does not exist or the balance is too low.
+    pub async fn change_balance(
+        serial_number: &str,
+        amount: i64,
+        note: Option<&str>,
+        created_by: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut transaction = conn.begin().await?;
+        let gift_card = sqlx::query_as!(
+            Self,
+            "UPDATE pass_type_gift_card SET balance=balance+$1, last_used_at=$2 WHERE serial_number=$3 AND balance+$1>=0 RETURNING *",
+            amount,
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let Some(gift_card) = gift_card else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "INSERT INTO gift_card_transactions (pass_serial_number, amount, balance_after, note, created_at, created_by) VALUES ($1, $2, $3, $4, $5, $6)",
+            serial_number,
+            amount,
+            gift_card.balance,
+            note,
+            now,
+            created_by
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Some(gift_card))
+    }
+}
+
+impl DbGiftCardTransaction {
+    /// The ledger of the gift card, newest first.
+    pub async fn from_serial_number(
+        serial_number: &str,
+        limit: i64,
+        offset: i64,
+        conn: &PgPool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM gift_card_transactions WHERE pass_serial_number=$1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3",
+            serial_number,
+            limit,
+            offset
+        )
+        .fetch_all(conn)
+        .await
+    }
+}
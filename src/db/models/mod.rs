@@ -1,7 +1,27 @@
+mod coupons;
+mod customer_view_links;
+mod device_logs;
 mod device_pass_registrations;
 mod devices;
+mod email_outbox;
+mod events;
+mod gift_cards;
+mod pass_auth_tokens;
+mod pass_recovery_tokens;
+mod pass_search;
 mod passes;
+mod scan_events;
 
+pub use coupons::DbPassTypeCoupon;
+pub use customer_view_links::DbCustomerViewLink;
+pub use device_logs::{DbDeviceLog, DbDeviceLogFilter};
 pub use device_pass_registrations::DbDevicePassRegistration;
 pub use devices::DbDevice;
-pub use passes::{DbPass, DbPassType, DbPassTypeHelper, DbPassTypeLoyality};
+pub use email_outbox::DbEmailOutbox;
+pub use events::{DbEvent, DbPassTypeEventTicket};
+pub use gift_cards::{DbGiftCardTransaction, DbPassTypeGiftCard};
+pub use pass_auth_tokens::DbPassAuthToken;
+pub use pass_recovery_tokens::DbPassRecoveryToken;
+pub use pass_search::{DbLoyalityPassSearch, DbLoyalityPassSortBy};
+pub use passes::{DbPass, DbPassHolderUpdate, DbPassType, DbPassTypeHelper, DbPassTypeLoyality};
+pub use scan_events::DbScanEvent;
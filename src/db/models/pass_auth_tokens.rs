@@ -0,0 +1,149 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+#[derive(FromRow, Debug)]
+pub struct DbPassAuthToken {
+    pub token_hash: String,
+    pub pass_serial_number: String,
+    pub created_at: NaiveDateTime,
+    /// Set for tokens that must be replaced the next time the pass is fetched.
+    pub needs_rotation: bool,
+    /// Set once a token got replaced. It is still accepted until then.
+    pub expires_at: Option<NaiveDateTime>,
+    /// Set once a token got replaced, derives its replacement from the token.
+    pub replacement_key: Option<String>,
+}
+
+impl DbPassAuthToken {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO pass_auth_tokens (token_hash, pass_serial_number, created_at, needs_rotation, expires_at) VALUES ($1, $2, $3, $4, $5)",
+            &self.token_hash,
+            &self.pass_serial_number,
+            self.created_at,
+            self.needs_rotation,
+            self.expires_at,
+        )
+        .execute(conn)
+        .await
+    }
+
+    /// Inserts the token unless it exists already, e.g. the replacement of a rotated token that
+    /// was fetched before.
+    pub async fn insert_if_missing<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO pass_auth_tokens (token_hash, pass_serial_number, created_at, needs_rotation, expires_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (token_hash) DO NOTHING",
+            &self.token_hash,
+            &self.pass_serial_number,
+            self.created_at,
+            self.needs_rotation,
+            self.expires_at,
+        )
+        .execute(conn)
+        .await
+    }
+
+    /// All tokens of the pass that are not expired at `now`. Passes of another pass type have no
+    /// valid tokens.
+    pub async fn valid_from_pass(
+        pass_type_id: &str,
+        pass_serial_number: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT t.* FROM pass_auth_tokens t INNER JOIN passes p ON p.serial_number=t.pass_serial_number WHERE p.pass_type_id=$1 AND t.pass_serial_number=$2 AND (t.expires_at IS NULL OR t.expires_at>$3)",
+            pass_type_id,
+            pass_serial_number,
+            now
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    /// Lets the token expire at `expires_at`, unless it already expires earlier. Returns the key
+    /// its replacement is derived with, which is `replacement_key` unless the token was retired
+    /// before.
+    pub async fn retire<'c>(
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+        replacement_key: &str,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<String, sqlx::Error> {
+        sqlx::query_scalar!(
+            "UPDATE pass_auth_tokens SET expires_at=LEAST(COALESCE(expires_at, $2), $2), replacement_key=COALESCE(replacement_key, $3) WHERE token_hash=$1 RETURNING replacement_key AS \"replacement_key!\"",
+            token_hash,
+            expires_at,
+            replacement_key
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Marks all active tokens of the pass to be replaced on the next fetch.
+    pub async fn request_rotation<'c>(
+        pass_serial_number: &str,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query!(
+            "UPDATE pass_auth_tokens SET needs_rotation=TRUE WHERE pass_serial_number=$1 AND expires_at IS NULL",
+            pass_serial_number
+        )
+        .execute(conn)
+        .await?
+        .rows_affected())
+    }
+
+    /// Marks all active tokens created before `created_before` to be replaced on the next fetch
+    /// and returns the serial numbers of the affected passes.
+    pub async fn request_rotation_created_before<'c>(
+        created_before: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "
+WITH rotated AS (
+    UPDATE pass_auth_tokens SET needs_rotation=TRUE
+    WHERE created_at<$1 AND expires_at IS NULL AND NOT needs_rotation
+    RETURNING pass_serial_number
+)
+SELECT DISTINCT pass_serial_number AS \"pass_serial_number!\" FROM rotated
+",
+            created_before
+        )
+        .fetch_all(conn)
+        .await
+    }
+
+    pub async fn delete_expired<'c>(
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(
+            sqlx::query!("DELETE FROM pass_auth_tokens WHERE expires_at<=$1", now)
+                .execute(conn)
+                .await?
+                .rows_affected(),
+        )
+    }
+
+    pub async fn delete_for_pass<'c>(
+        pass_serial_number: &str,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query!(
+            "DELETE FROM pass_auth_tokens WHERE pass_serial_number=$1",
+            pass_serial_number
+        )
+        .execute(conn)
+        .await?
+        .rows_affected())
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor};
+
+#[derive(FromRow, Debug)]
+pub struct DbPassRecoveryToken {
+    pub token_hash: String,
+    pub pass_serial_number: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub created_by: String,
+}
+
+impl DbPassRecoveryToken {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO pass_recovery_tokens (token_hash, pass_serial_number, created_at, expires_at, created_by) VALUES ($1, $2, $3, $4, $5)",
+            &self.token_hash,
+            &self.pass_serial_number,
+            self.created_at,
+            self.expires_at,
+            &self.created_by,
+        )
+        .execute(conn)
+        .await
+    }
+
+    /// Deletes the token if it is still valid and returns it, so each token can only be used
+    /// once.
+    pub async fn consume<'c>(
+        token_hash: &str,
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "DELETE FROM pass_recovery_tokens WHERE token_hash=$1 AND expires_at>$2 RETURNING *",
+            token_hash,
+            now
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use super::DbPassTypeLoyality;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum DbLoyalityPassSortBy {
+    /// Best trigram match first. Falls back to `CreatedAt` without a search query.
+    #[default]
+    Relevance,
+    CreatedAt,
+    LastUsedAt,
+    PassHolderName,
+    CurrentPoints,
+    AlreadyRedeemed,
+}
+
+#[derive(Debug, Default)]
+pub struct DbLoyalityPassSearch {
+    /// Fuzzy search over the name, email and phone number of the pass holder.
+    pub query: Option<String>,
+    pub pass_holder_name: Option<String>,
+    pub pass_holder_email: Option<String>,
+    pub pass_holder_phone: Option<String>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub min_points: Option<i32>,
+    pub max_points: Option<i32>,
+    pub sort_by: DbLoyalityPassSortBy,
+    pub descending: bool,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl DbLoyalityPassSearch {
+    fn push_conditions(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        builder.push(" WHERE TRUE");
+
+        if let Some(query) = &self.query {
+            let pattern = like_pattern(query);
+
+            builder
+                .push(" AND (l.pass_holder_name % ")
+                .push_bind(query.clone())
+                .push(" OR l.pass_holder_email % ")
+                .push_bind(query.clone())
+                .push(" OR l.pass_holder_phone % ")
+                .push_bind(query.clone())
+                .push(" OR l.pass_holder_name ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR l.pass_holder_email ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR l.pass_holder_phone ILIKE ")
+                .push_bind(pattern)
+                .push(")");
+        }
+
+        for (column, value) in [
+            ("l.pass_holder_name", &self.pass_holder_name),
+            ("l.pass_holder_email", &self.pass_holder_email),
+            ("l.pass_holder_phone", &self.pass_holder_phone),
+        ] {
+            if let Some(value) = value {
+                builder
+                    .push(format!(" AND {column} ILIKE "))
+                    .push_bind(like_pattern(value));
+            }
+        }
+
+        if let Some(created_after) = self.created_after {
+            builder
+                .push(" AND p.created_at >= ")
+                .push_bind(created_after);
+        }
+
+        if let Some(created_before) = self.created_before {
+            builder
+                .push(" AND p.created_at < ")
+                .push_bind(created_before);
+        }
+
+        if let Some(min_points) = self.min_points {
+            builder
+                .push(" AND l.current_points >= ")
+                .push_bind(min_points);
+        }
+
+        if let Some(max_points) = self.max_points {
+            builder
+                .push(" AND l.current_points <= ")
+                .push_bind(max_points);
+        }
+    }
+
+    fn push_order(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+
+        builder.push(" ORDER BY ");
+
+        match (self.sort_by, &self.query) {
+            (DbLoyalityPassSortBy::Relevance, Some(query)) => {
+                builder
+                    .push("GREATEST(similarity(l.pass_holder_name, ")
+                    .push_bind(query.clone())
+                    .push("), similarity(COALESCE(l.pass_holder_email, ''), ")
+                    .push_bind(query.clone())
+                    .push("), similarity(COALESCE(l.pass_holder_phone, ''), ")
+                    .push_bind(query.clone())
+                    .push(")) DESC");
+            }
+            (DbLoyalityPassSortBy::Relevance, None) | (DbLoyalityPassSortBy::CreatedAt, _) => {
+                builder.push(format!("p.created_at {direction}"));
+            }
+            (DbLoyalityPassSortBy::LastUsedAt, _) => {
+                builder.push(format!("l.last_used_at {direction} NULLS LAST"));
+            }
+            (DbLoyalityPassSortBy::PassHolderName, _) => {
+                builder.push(format!("l.pass_holder_name {direction}"));
+            }
+            (DbLoyalityPassSortBy::CurrentPoints, _) => {
+                builder.push(format!("l.current_points {direction}"));
+            }
+            (DbLoyalityPassSortBy::AlreadyRedeemed, _) => {
+                builder.push(format!("l.already_redeemed {direction}"));
+            }
+        }
+
+        // Keeps the pagination stable if the sorted column has duplicates.
+        builder.push(", l.serial_number ASC");
+    }
+}
+
+impl DbPassTypeLoyality {
+    /// Returns one page of the passes matching `search` together with the total amount of matches.
+    pub async fn search(
+        search: &DbLoyalityPassSearch,
+        conn: &PgPool,
+    ) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        let mut count_builder = QueryBuilder::new(
+            "SELECT COUNT(*) FROM pass_type_loyality l INNER JOIN passes p ON p.serial_number=l.serial_number",
+        );
+        search.push_conditions(&mut count_builder);
+
+        let total: i64 = count_builder.build_query_scalar().fetch_one(conn).await?;
+
+        let mut builder = QueryBuilder::new(
+            "SELECT l.* FROM pass_type_loyality l INNER JOIN passes p ON p.serial_number=l.serial_number",
+        );
+        search.push_conditions(&mut builder);
+        search.push_order(&mut builder);
+        builder
+            .push(" LIMIT ")
+            .push_bind(search.limit)
+            .push(" OFFSET ")
+            .push_bind(search.offset);
+
+        let passes = builder.build_query_as().fetch_all(conn).await?;
+
+        Ok((passes, total))
+    }
+}
+
+/// Builds a `ILIKE` pattern matching `value` anywhere, with its wildcards escaped.
+fn like_pattern(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{escaped}%")
+}
@@ -1,5 +1,7 @@
 use chrono::{NaiveDateTime, Utc};
-use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgPool};
+use sqlx::{postgres::PgQueryResult, prelude::FromRow, PgExecutor, PgPool};
+
+use super::{DbPassTypeCoupon, DbPassTypeEventTicket, DbPassTypeGiftCard};
 
 #[derive(FromRow)]
 pub struct DbPassTypeLoyality {
@@ -9,17 +11,32 @@ pub struct DbPassTypeLoyality {
     pub current_points: i32,
     pub pass_holder_name: String,
     pub last_used_at: Option<NaiveDateTime>,
+    pub pass_holder_email: Option<String>,
+    pub pass_holder_phone: Option<String>,
+    /// All points ever collected, `current_points` is reset with each redemption.
+    pub lifetime_points: i32,
+    /// Id of the reached membership tier.
+    pub tier: Option<String>,
+    pub tier_reached_at: Option<NaiveDateTime>,
 }
 
 impl DbPassTypeLoyality {
-    pub async fn insert(&self, conn: &PgPool) -> Result<PgQueryResult, sqlx::Error> {
-        sqlx::query!("INSERT INTO pass_type_loyality (serial_number, already_redeemed, total_points, current_points, pass_holder_name, last_used_at) VALUES ($1, $2, $3, $4, $5, $6)",
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query!("INSERT INTO pass_type_loyality (serial_number, already_redeemed, total_points, current_points, pass_holder_name, last_used_at, pass_holder_email, pass_holder_phone, lifetime_points, tier, tier_reached_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
     self.serial_number.clone(),
     self.already_redeemed,
     self.total_points,
     self.current_points,
     &self.pass_holder_name,
     self.last_used_at,
+    self.pass_holder_email.as_deref(),
+    self.pass_holder_phone.as_deref(),
+    self.lifetime_points,
+    self.tier.as_deref(),
+    self.tier_reached_at,
             )
             .execute(conn).await
     }
@@ -50,17 +67,29 @@ impl DbPassTypeLoyality {
         .await
     }
 
+    /// Adds `points` to the pass if the result stays within `total_points` and returns the
+    /// updated row. Returns `None` if the pass does not exist or the limit would be exceeded.
     pub async fn add_points(
         serial_number: &str,
         points: i32,
         conn: &PgPool,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<Option<Self>, sqlx::Error> {
         let now = Utc::now().naive_utc();
 
         let mut transaction = conn.begin().await?;
-        sqlx::query!("UPDATE pass_type_loyality SET current_points=current_points+$1, last_used_at=$2 WHERE serial_number=$3", points, now, serial_number)
-            .execute(&mut *transaction)
-            .await?;
+        let pass = sqlx::query_as!(
+            Self,
+            "UPDATE pass_type_loyality SET current_points=current_points+$1, lifetime_points=lifetime_points+$1, last_used_at=$2 WHERE serial_number=$3 AND current_points+$1<=total_points RETURNING *",
+            points,
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        if pass.is_none() {
+            return Ok(None);
+        }
 
         sqlx::query!(
             "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
@@ -72,35 +101,185 @@ impl DbPassTypeLoyality {
 
         transaction.commit().await?;
 
-        Ok(())
+        Ok(pass)
     }
 
-    pub async fn redeem_bonus(serial_number: &str, conn: &PgPool) -> Result<(), sqlx::Error> {
+    /// Resets the points of a full pass and counts the redemption. Returns `None` if the pass
+    /// does not exist or does not have all points collected.
+    pub async fn redeem_bonus(
+        serial_number: &str,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
         let now = Utc::now().naive_utc();
 
-        sqlx::query!("UPDATE pass_type_loyality SET current_points=0, already_redeemed=already_redeemed+1, last_used_at=$1 WHERE serial_number=$2", now, serial_number)
-            .execute(conn).await?;
+        let mut transaction = conn.begin().await?;
+        let pass = sqlx::query_as!(
+            Self,
+            "UPDATE pass_type_loyality SET current_points=0, already_redeemed=already_redeemed+1, last_used_at=$1 WHERE serial_number=$2 AND current_points=total_points RETURNING *",
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
 
-        Ok(())
+        if pass.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(pass)
+    }
+}
+
+impl DbPassTypeLoyality {
+    /// Moves the pass into the membership tier `tier`.
+    pub async fn set_tier(
+        serial_number: &str,
+        tier: &str,
+        now: NaiveDateTime,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut transaction = conn.begin().await?;
+        let pass = sqlx::query_as!(
+            Self,
+            "UPDATE pass_type_loyality SET tier=$1, tier_reached_at=$2 WHERE serial_number=$3 RETURNING *",
+            tier,
+            now,
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        if pass.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(pass)
+    }
+}
+
+/// Changes to the pass holder details. `None` keeps the current value, the inner `None` of the
+/// optional contact fields removes them.
+#[derive(Debug, Default)]
+pub struct DbPassHolderUpdate {
+    pub pass_holder_name: Option<String>,
+    pub pass_holder_email: Option<Option<String>>,
+    pub pass_holder_phone: Option<Option<String>>,
+}
+
+impl DbPassTypeLoyality {
+    pub async fn update_pass_holder(
+        serial_number: &str,
+        update: &DbPassHolderUpdate,
+        conn: &PgPool,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now().naive_utc();
+
+        let mut transaction = conn.begin().await?;
+        let pass = sqlx::query_as!(
+            Self,
+            "
+UPDATE pass_type_loyality SET
+    pass_holder_name = COALESCE($1, pass_holder_name),
+    pass_holder_email = CASE WHEN $2 THEN $3 ELSE pass_holder_email END,
+    pass_holder_phone = CASE WHEN $4 THEN $5 ELSE pass_holder_phone END
+WHERE serial_number = $6
+RETURNING *
+",
+            update.pass_holder_name.as_deref(),
+            update.pass_holder_email.is_some(),
+            update.pass_holder_email.clone().flatten(),
+            update.pass_holder_phone.is_some(),
+            update.pass_holder_phone.clone().flatten(),
+            serial_number
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        if pass.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=$2",
+            now,
+            serial_number
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(pass)
     }
 }
 
 pub enum DbPassType {
     Loyality(DbPassTypeLoyality),
+    Coupon(DbPassTypeCoupon),
+    GiftCard(DbPassTypeGiftCard),
+    EventTicket(DbPassTypeEventTicket),
 }
 
 impl DbPassType {
-    pub async fn insert<T>(&self, conn: &PgPool) -> Result<PgQueryResult, sqlx::Error> {
+    pub fn serial_number(&self) -> &str {
+        match self {
+            Self::Loyality(l) => &l.serial_number,
+            Self::Coupon(c) => &c.serial_number,
+            Self::GiftCard(g) => &g.serial_number,
+            Self::EventTicket(e) => &e.serial_number,
+        }
+    }
+
+    /// The name of the customer the pass was issued to, if it has one.
+    pub fn pass_holder_name(&self) -> Option<&str> {
+        match self {
+            Self::Loyality(l) => Some(&l.pass_holder_name),
+            Self::Coupon(_) => None,
+            Self::GiftCard(g) => g.pass_holder_name.as_deref(),
+            Self::EventTicket(e) => e.attendee_name.as_deref(),
+        }
+    }
+
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
         match self {
             Self::Loyality(l) => l.insert(conn).await,
+            Self::Coupon(c) => c.insert(conn).await,
+            Self::GiftCard(g) => g.insert(conn).await,
+            Self::EventTicket(e) => e.insert(conn).await,
         }
     }
 }
 
-#[derive(sqlx::Type, Clone, Debug)]
+#[derive(sqlx::Type, Clone, Debug, PartialEq, Eq)]
 #[sqlx(rename_all = "SCREAMING_SNAKE_CASE", type_name = "pass_type")]
 pub enum DbPassTypeHelper {
     Loyality,
+    Coupon,
+    GiftCard,
+    EventTicket,
 }
 
 impl DbPassTypeHelper {
@@ -113,14 +292,26 @@ impl DbPassTypeHelper {
             Self::Loyality => Ok(DbPassType::Loyality(
                 DbPassTypeLoyality::from_serial_number(serial_number, conn).await?,
             )),
+            Self::Coupon => Ok(DbPassType::Coupon(
+                DbPassTypeCoupon::from_serial_number(serial_number, conn).await?,
+            )),
+            Self::GiftCard => Ok(DbPassType::GiftCard(
+                DbPassTypeGiftCard::from_serial_number(serial_number, conn).await?,
+            )),
+            Self::EventTicket => Ok(DbPassType::EventTicket(
+                DbPassTypeEventTicket::from_serial_number(serial_number, conn).await?,
+            )),
         }
     }
 }
 
-impl From<DbPassType> for DbPassTypeHelper {
-    fn from(value: DbPassType) -> Self {
+impl From<&DbPassType> for DbPassTypeHelper {
+    fn from(value: &DbPassType) -> Self {
         match value {
             DbPassType::Loyality(_) => Self::Loyality,
+            DbPassType::Coupon(_) => Self::Coupon,
+            DbPassType::GiftCard(_) => Self::GiftCard,
+            DbPassType::EventTicket(_) => Self::EventTicket,
         }
     }
 }
@@ -129,10 +320,11 @@ impl From<DbPassType> for DbPassTypeHelper {
 pub struct DbPass {
     pub serial_number: String,
     pub pass_type_id: String,
-    pub auth_token: String,
     pub last_updated_at: chrono::NaiveDateTime,
     pub created_at: chrono::NaiveDateTime,
     pub r#type: DbPassTypeHelper,
+    /// Short code shown to the customer and encoded in the barcode instead of the serial number.
+    pub member_code: String,
 }
 
 impl DbPass {
@@ -146,15 +338,18 @@ impl DbPass {
         .unwrap_or(false))
     }
 
-    pub async fn insert(&self, conn: &PgPool) -> Result<PgQueryResult, sqlx::Error> {
+    pub async fn insert<'c>(
+        &self,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
         sqlx::query!(
-            "INSERT INTO passes (serial_number, pass_type_id, auth_token, created_at, last_updated_at, type) VALUES ($1, $2, $3, $4, $5, $6)",
+            "INSERT INTO passes (serial_number, pass_type_id, created_at, last_updated_at, type, member_code) VALUES ($1, $2, $3, $4, $5, $6)",
             &self.serial_number,
             &self.pass_type_id,
-            &self.auth_token,
             self.created_at,
             self.last_updated_at,
-            self.r#type.clone() as _
+            self.r#type.clone() as _,
+            &self.member_code,
         )
             .execute(conn).await
     }
@@ -169,13 +364,25 @@ impl DbPass {
             .await
     }
 
+    pub async fn serial_number_from_member_code(
+        member_code: &str,
+        conn: &PgPool,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            "SELECT serial_number FROM passes WHERE member_code=$1",
+            member_code
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
     pub async fn from_serial_number_optional(
         serial_number: &str,
         conn: &PgPool,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Self,
-            "SELECT serial_number, auth_token, created_at, last_updated_at, pass_type_id, type as \"type: _\" FROM passes WHERE serial_number=$1",
+            "SELECT serial_number, created_at, last_updated_at, pass_type_id, type as \"type: _\", member_code FROM passes WHERE serial_number=$1",
             serial_number
         )
         .fetch_optional(conn)
@@ -191,34 +398,32 @@ impl DbPass {
         if let Some(lu) = last_updated_at {
             sqlx::query_as!(
                 Self,
-            "SELECT serial_number, auth_token, p.created_at, last_updated_at, pass_type_id, type as \"type: _\" FROM passes p INNER JOIN device_pass_registrations dpr ON p.serial_number=dpr.pass_serial_number WHERE pass_type_id=$1 AND device_library_id=$2 AND last_updated_at>=$3", pass_type_id, device_library_id, lu
+            "SELECT serial_number, p.created_at, last_updated_at, pass_type_id, type as \"type: _\", member_code FROM passes p INNER JOIN device_pass_registrations dpr ON p.serial_number=dpr.pass_serial_number WHERE pass_type_id=$1 AND device_library_id=$2 AND last_updated_at>=$3", pass_type_id, device_library_id, lu
         )
         .fetch_all(conn)
         .await
         } else {
             sqlx::query_as!(
                 Self,
-            "SELECT serial_number, auth_token, p.created_at, last_updated_at, pass_type_id, type as \"type: _\" FROM passes p INNER JOIN device_pass_registrations dpr ON p.serial_number=dpr.pass_serial_number WHERE pass_type_id=$1 AND device_library_id=$2", pass_type_id, device_library_id,
+            "SELECT serial_number, p.created_at, last_updated_at, pass_type_id, type as \"type: _\", member_code FROM passes p INNER JOIN device_pass_registrations dpr ON p.serial_number=dpr.pass_serial_number WHERE pass_type_id=$1 AND device_library_id=$2", pass_type_id, device_library_id,
         )
         .fetch_all(conn)
         .await
         }
     }
 
-    pub async fn count_of_devices(serial_number: &str, conn: &PgPool) -> Result<i64, sqlx::Error> {
-        Ok(sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM device_pass_registrations WHERE pass_serial_number = $1",
-            serial_number
+    pub async fn touch<'c>(
+        serial_numbers: &[String],
+        now: NaiveDateTime,
+        conn: impl PgExecutor<'c>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE passes SET last_updated_at=$1 WHERE serial_number=ANY($2)",
+            now,
+            serial_numbers
         )
-        .fetch_one(conn)
-        .await?
-        .unwrap_or(0))
-    }
-
-    pub async fn delete(serial_number: &str, conn: &PgPool) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM passes WHERE serial_number=$1", serial_number)
-            .execute(conn)
-            .await?;
+        .execute(conn)
+        .await?;
 
         Ok(())
     }
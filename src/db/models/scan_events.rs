@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use sqlx::{prelude::FromRow, PgPool};
+
+/// A scan of a pass at the counter.
+#[derive(FromRow, Debug)]
+pub struct DbScanEvent {
+    pub id: i64,
+    pub pass_serial_number: String,
+    pub scanned_at: NaiveDateTime,
+    /// The admin who scanned the pass.
+    pub scanned_by: String,
+    /// The store the pass was scanned in, as sent by the scanner.
+    pub store: Option<String>,
+    /// Whether the scanned barcode was signed.
+    pub signed: bool,
+}
+
+impl DbScanEvent {
+    pub async fn insert(
+        pass_serial_number: &str,
+        scanned_at: NaiveDateTime,
+        scanned_by: &str,
+        store: Option<&str>,
+        signed: bool,
+        conn: &PgPool,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "INSERT INTO scan_events (pass_serial_number, scanned_at, scanned_by, store, signed) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            pass_serial_number,
+            scanned_at,
+            scanned_by,
+            store,
+            signed,
+        )
+        .fetch_one(conn)
+        .await
+    }
+
+    /// The latest scans of the pass, newest first.
+    pub async fn from_serial_number(
+        pass_serial_number: &str,
+        limit: i64,
+        conn: &PgPool,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Self,
+            "SELECT * FROM scan_events WHERE pass_serial_number=$1 ORDER BY scanned_at DESC, id DESC LIMIT $2",
+            pass_serial_number,
+            limit
+        )
+        .fetch_all(conn)
+        .await
+    }
+}
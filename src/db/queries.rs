@@ -86,30 +86,6 @@ SELECT EXISTS (
     .unwrap_or(false))
 }
 
-pub async fn correct_serial_number_auth_token(
-    serial_number: &str,
-    auth_token: &str,
-    pool: &PgPool,
-) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query_scalar!(
-        "
-SELECT EXISTS (
-    SELECT 1 
-    FROM passes 
-    WHERE serial_number = $1
-    AND auth_token = $2
-);
-",
-        serial_number,
-        auth_token
-    )
-    .fetch_one(pool)
-    .await?
-    .unwrap_or(false);
-
-    Ok(result)
-}
-
 pub async fn push_tokens_from_serial_number(
     serial_number: &str,
     conn: &PgPool,
@@ -130,3 +106,40 @@ pub async fn remove_devices_with_push_tokens(
 
     Ok(())
 }
+
+/// Counts a hit for `bucket` in the window starting at `window_start` and returns the amount of
+/// hits in that window. Hits of older windows are discarded.
+pub async fn hit_rate_limit_bucket(
+    bucket: &str,
+    window_start: chrono::NaiveDateTime,
+    conn: &PgPool,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!(
+        "
+INSERT INTO rate_limit_buckets (bucket, window_start, hits)
+VALUES ($1, $2, 1)
+ON CONFLICT (bucket)
+DO UPDATE SET
+    hits = CASE WHEN rate_limit_buckets.window_start = EXCLUDED.window_start THEN rate_limit_buckets.hits + 1 ELSE 1 END,
+    window_start = EXCLUDED.window_start
+RETURNING hits
+",
+        bucket,
+        window_start
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn delete_stale_rate_limit_buckets(
+    window_start_before: chrono::NaiveDateTime,
+    conn: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query!(
+        "DELETE FROM rate_limit_buckets WHERE window_start < $1",
+        window_start_before
+    )
+    .execute(conn)
+    .await?
+    .rows_affected())
+}
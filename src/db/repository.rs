@@ -0,0 +1,78 @@
+use sqlx::{PgConnection, PgPool};
+
+use crate::Error;
+
+use super::{DbPass, DbPassType, DbPassTypeHelper};
+
+/// Inserts a pass together with its type specific data. Both rows are written through the same
+/// connection, so callers should pass a transaction to never end up with a pass without data.
+pub async fn insert_pass(
+    pass: &DbPass,
+    pass_type: &DbPassType,
+    conn: &mut PgConnection,
+) -> crate::Result<()> {
+    let data_type = DbPassTypeHelper::from(pass_type);
+    if pass.r#type != data_type {
+        return Err(Error::PassTypeMismatch {
+            serial_number: pass.serial_number.clone(),
+            pass_type: format!("{:?}", pass.r#type),
+            data_type: format!("{data_type:?}"),
+        });
+    }
+
+    pass.insert(&mut *conn).await?;
+    pass_type.insert(&mut *conn).await?;
+
+    Ok(())
+}
+
+/// Serial numbers of all passes that are missing the data of their type.
+pub async fn orphaned_passes(conn: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "
+SELECT p.serial_number
+FROM passes p
+WHERE (
+    p.type = 'LOYALITY'
+    AND NOT EXISTS (
+        SELECT 1
+        FROM pass_type_loyality l
+        WHERE l.serial_number = p.serial_number
+    )
+) OR (
+    p.type = 'COUPON'
+    AND NOT EXISTS (
+        SELECT 1
+        FROM pass_type_coupon c
+        WHERE c.serial_number = p.serial_number
+    )
+) OR (
+    p.type = 'GIFT_CARD'
+    AND NOT EXISTS (
+        SELECT 1
+        FROM pass_type_gift_card g
+        WHERE g.serial_number = p.serial_number
+    )
+) OR (
+    p.type = 'EVENT_TICKET'
+    AND NOT EXISTS (
+        SELECT 1
+        FROM pass_type_event_ticket e
+        WHERE e.serial_number = p.serial_number
+    )
+)
+"
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn delete_passes(serial_numbers: &[String], conn: &PgPool) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query!(
+        "DELETE FROM passes WHERE serial_number=ANY($1)",
+        serial_numbers
+    )
+    .execute(conn)
+    .await?
+    .rows_affected())
+}
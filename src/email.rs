@@ -0,0 +1,253 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use lettre::{
+    message::{
+        header::{ContentTransferEncoding, ContentType},
+        Attachment, Body, Mailbox, MultiPart, SinglePart,
+    },
+    transport::smtp::authentication::Credentials,
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{Error, Result};
+
+/// How the connection to the SMTP server is secured.
+#[derive(serde::Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plaintext connection upgraded with `STARTTLS`, usually on port 587.
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465.
+    Tls,
+    /// Plaintext only, for local relays.
+    None,
+}
+
+pub struct SmtpSettings<'a> {
+    pub host: &'a str,
+    pub port: Option<u16>,
+    pub tls: SmtpTls,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+}
+
+/// An email with the pass attached, as sent to the customer.
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub attachment: Option<EmailAttachment>,
+}
+
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// An email delivered to the [`MailTransport::Memory`] transport.
+#[derive(Debug, Clone)]
+pub struct SentEmail {
+    pub to: String,
+    pub subject: String,
+    /// The complete message, as it would be sent to the SMTP server.
+    pub message: Vec<u8>,
+}
+
+/// Keeps the sent emails, shared between the mailer and whoever inspects them. Not configurable,
+/// tests create the mailer with a clone of the mailbox.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMailbox(Arc<Mutex<Vec<SentEmail>>>);
+
+impl MemoryMailbox {
+    pub fn sent(&self) -> Vec<SentEmail> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+pub enum MailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    /// Writes each email as `.eml` file into a directory, for local development.
+    File(AsyncFileTransport<Tokio1Executor>),
+    /// Keeps the emails in memory, for tests.
+    Memory(MemoryMailbox),
+}
+
+impl MailTransport {
+    pub fn smtp(settings: SmtpSettings) -> Result<Self> {
+        let mut builder = match settings.tls {
+            SmtpTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(settings.host)
+                    .map_err(|e| Error::Email(e.to_string()))?
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(settings.host)
+                .map_err(|e| Error::Email(e.to_string()))?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.host),
+        };
+
+        if let Some(port) = settings.port {
+            builder = builder.port(port);
+        }
+
+        if let Some(username) = settings.username {
+            builder = builder.credentials(Credentials::new(
+                username.to_string(),
+                settings.password.unwrap_or_default().to_string(),
+            ));
+        }
+
+        Ok(Self::Smtp(builder.build()))
+    }
+
+    pub fn file(dir: &str) -> Result<Self> {
+        if !Path::new(dir).is_dir() {
+            return Err(Error::Other(format!("directory {dir} does not exist")));
+        }
+
+        Ok(Self::File(AsyncFileTransport::new(dir)))
+    }
+}
+
+impl std::fmt::Debug for MailTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Smtp(_) => write!(f, "MailTransport::Smtp"),
+            Self::File(_) => write!(f, "MailTransport::File"),
+            Self::Memory(_) => write!(f, "MailTransport::Memory"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Mailer {
+    from: Mailbox,
+    transport: MailTransport,
+}
+
+/// Parses an email address, e.g. `Boulder Bubbletea <hello@example.com>`.
+pub fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .trim()
+        .parse()
+        .map_err(|e| Error::InvalidRequest(format!("invalid email address {address:?}: {e}")))
+}
+
+impl Mailer {
+    pub fn new(from: &str, transport: MailTransport) -> Result<Self> {
+        Ok(Self {
+            from: parse_mailbox(from)?,
+            transport,
+        })
+    }
+
+    pub async fn send(&self, email: OutgoingEmail) -> Result<()> {
+        let to = parse_mailbox(&email.to)?;
+
+        let text = SinglePart::plain(email.body);
+        let builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.clone());
+
+        let message = match email.attachment {
+            Some(attachment) => builder.multipart(
+                MultiPart::mixed().singlepart(text).singlepart(
+                    Attachment::new(attachment.filename).body(
+                        // Passes are zip files, lettre would pick 7bit for some of them.
+                        Body::new_with_encoding(attachment.data, ContentTransferEncoding::Base64)
+                            .map_err(|_| Error::Email("can't encode the attachment".into()))?,
+                        ContentType::parse(attachment.content_type)
+                            .map_err(|e| Error::Email(e.to_string()))?,
+                    ),
+                ),
+            ),
+            None => builder.singlepart(text),
+        }
+        .map_err(|e| Error::Email(e.to_string()))?;
+
+        match &self.transport {
+            MailTransport::Smtp(transport) => {
+                transport
+                    .send(message)
+                    .await
+                    .map_err(|e| Error::Email(e.to_string()))?;
+            }
+            MailTransport::File(transport) => {
+                transport
+                    .send(message)
+                    .await
+                    .map_err(|e| Error::Email(e.to_string()))?;
+            }
+            MailTransport::Memory(mailbox) => {
+                mailbox.0.lock().unwrap().push(SentEmail {
+                    to: email.to,
+                    subject: email.subject,
+                    message: message.formatted(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmailAttachment, MailTransport, Mailer, MemoryMailbox, OutgoingEmail};
+
+    #[tokio::test]
+    async fn sends_emails_to_the_memory_mailbox() {
+        let mailbox = MemoryMailbox::default();
+        let mailer = Mailer::new(
+            "Boulder Bubbletea <hello@example.com>",
+            MailTransport::Memory(mailbox.clone()),
+        )
+        .unwrap();
+
+        mailer
+            .send(OutgoingEmail {
+                to: "kim@example.com".into(),
+                subject: "Dein Pass".into(),
+                body: "Hallo Kim".into(),
+                attachment: Some(EmailAttachment {
+                    filename: "pass.pkpass".into(),
+                    content_type: "application/vnd.apple.pkpass",
+                    data: vec![0x50, 0x4b, 0x03, 0x04, 0xff, 0x00],
+                }),
+            })
+            .await
+            .unwrap();
+
+        let sent = mailbox.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "kim@example.com");
+        assert_eq!(sent[0].subject, "Dein Pass");
+
+        let message = String::from_utf8_lossy(&sent[0].message);
+        assert!(message.contains("Content-Type: application/vnd.apple.pkpass"));
+        assert!(message.contains("Content-Transfer-Encoding: base64"));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_recipients() {
+        let mailbox = MemoryMailbox::default();
+        let mailer =
+            Mailer::new("hello@example.com", MailTransport::Memory(mailbox.clone())).unwrap();
+
+        let result = mailer
+            .send(OutgoingEmail {
+                to: "not an address".into(),
+                subject: "Dein Pass".into(),
+                body: "Hallo".into(),
+                attachment: None,
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert!(mailbox.sent().is_empty());
+    }
+}
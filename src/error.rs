@@ -3,6 +3,7 @@ use axum::{
     response::IntoResponse,
 };
 use axum_extra::typed_header::TypedHeaderRejection;
+use chrono::NaiveDateTime;
 
 use crate::http::ClientError;
 
@@ -46,20 +47,60 @@ pub enum Error {
     #[error("image error: {0}")]
     Image(#[from] image::ImageError),
 
+    #[error("email error: {0}")]
+    Email(String),
+
+    #[error("pass type {0} is not configured")]
+    PassTypeNotConfigured(String),
+
+    #[error("pass {serial_number} has type {pass_type} but got data of type {data_type}")]
+    PassTypeMismatch {
+        serial_number: String,
+        pass_type: String,
+        data_type: String,
+    },
+
     #[error("pass not found")]
     PassNotFound,
 
+    #[error("recovery token is invalid or expired")]
+    RecoveryTokenInvalid,
+
+    #[error("rate limited, retry after {retry_after}s")]
+    RateLimited { retry_after: u64 },
+
     #[error("invalid amount of points")]
     InvalidAmountOfPoints,
 
+    #[error("coupon can't be redeemed: {0}")]
+    CouponNotRedeemable(&'static str),
+
+    #[error("insufficient balance, {balance} left")]
+    InsufficientBalance { balance: i64 },
+
+    #[error("barcode is invalid")]
+    BarcodeInvalid,
+
+    #[error("barcode belongs to an older version of the pass")]
+    BarcodeOutdated,
+
+    #[error("barcode is not signed")]
+    BarcodeUnsigned,
+
+    #[error("event not found")]
+    EventNotFound,
+
+    #[error("ticket already checked in at {checked_in_at}")]
+    TicketAlreadyCheckedIn { checked_in_at: NaiveDateTime },
+
     #[error("jwk error: {0}")]
     OidcValidate(#[from] oidc_jwt_validator::ValidationError),
 
     #[error("jwk build error: {0}")]
     OidcValidateBuild(#[from] oidc_jwt_validator::FetchError),
 
-    #[error("env error: {0}")]
-    Env(#[from] envy::Error),
+    #[error("invalid configuration:\n  {}", .0.join("\n  "))]
+    Config(Vec<String>),
 }
 
 impl IntoResponse for Error {
@@ -1,4 +1,8 @@
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{TimeZone, Utc};
 use serde::Serialize;
 use serde_json::Value;
 use uuid::Uuid;
@@ -25,6 +29,10 @@ pub struct ClientError {
     /// The unique id of the request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_message: Option<&'static str>,
+
+    /// Seconds after which the request can be retried, sent as `Retry-After` header.
+    #[serde(skip)]
+    pub retry_after: Option<u64>,
 }
 
 impl ClientError {
@@ -34,6 +42,7 @@ impl ClientError {
             error_details: Some("Something went wrong on our side".into()),
             status: StatusCode::INTERNAL_SERVER_ERROR,
             request_id: None,
+            retry_after: None,
             client_message: Some("Something went wrong"),
         }
     }
@@ -45,10 +54,13 @@ impl From<Error> for ClientError {
             Error::Unknown
             | Error::IO(_)
             | Error::OpenSsl(_)
-            | Error::Env(_)
+            | Error::Config(_)
             | Error::AppleApn(_)
             | Error::OidcValidateBuild(_)
             | Error::Image(_)
+            | Error::Email(_)
+            | Error::PassTypeNotConfigured(_)
+            | Error::PassTypeMismatch { .. }
             | Error::Database(_)
             | Error::Other(_)
             | Error::DatabaseMigration(_) => Self::new_internal_server_error(),
@@ -62,6 +74,7 @@ impl From<Error> for ClientError {
                     ),
                     status: StatusCode::EXPECTATION_FAILED,
                     request_id: None,
+                    retry_after: None,
                     client_message: Some(
                         "The auth token is not valid. Please try to log out and in again.",
                     ),
@@ -75,13 +88,31 @@ impl From<Error> for ClientError {
                 error_details: Some("this pass does not exist".into()),
                 status: StatusCode::NOT_FOUND,
                 request_id: None,
+                retry_after: None,
                 client_message: Some("the pass you search for does not exist."),
             },
+            Error::RecoveryTokenInvalid => Self {
+                error_name: "RecoveryTokenInvalid",
+                error_details: Some("the recovery token does not exist or is expired".into()),
+                status: StatusCode::NOT_FOUND,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("This link is invalid or expired. Please ask for a new one."),
+            },
+            Error::RateLimited { retry_after } => Self {
+                error_name: "RateLimited",
+                error_details: Some("too many requests".into()),
+                status: StatusCode::TOO_MANY_REQUESTS,
+                request_id: None,
+                retry_after: Some(retry_after),
+                client_message: Some("Too many requests. Please try again later."),
+            },
             Error::InvalidRequest(message) => Self {
                 error_name: "InvalidRequest",
                 error_details: Some(message.into()),
                 status: StatusCode::BAD_REQUEST,
                 request_id: None,
+                retry_after: None,
                 client_message: None,
             },
             Error::InvalidAmountOfPoints => Self {
@@ -89,12 +120,76 @@ impl From<Error> for ClientError {
                 error_details: Some("the amount of points entered are not valid".into()),
                 status: StatusCode::BAD_REQUEST,
                 request_id: None,
+                retry_after: None,
                 client_message: Some("The amount of points entered are not valid. Are they maybe lower / higher than possible?"),
             },
+            Error::CouponNotRedeemable(reason) => Self {
+                error_name: "CouponNotRedeemable",
+                error_details: Some(reason.into()),
+                status: StatusCode::CONFLICT,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("The coupon can't be redeemed."),
+            },
+            Error::InsufficientBalance { balance } => Self {
+                error_name: "InsufficientBalance",
+                error_details: Some(serde_json::json!({ "balance": balance })),
+                status: StatusCode::CONFLICT,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("The balance of the gift card is too low."),
+            },
+            Error::BarcodeInvalid => Self {
+                error_name: "BarcodeInvalid",
+                error_details: Some("the barcode is not a valid barcode of a pass".into()),
+                status: StatusCode::UNPROCESSABLE_ENTITY,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("The barcode is not valid."),
+            },
+            Error::BarcodeOutdated => Self {
+                error_name: "BarcodeOutdated",
+                error_details: Some("the barcode belongs to an older version of the pass".into()),
+                status: StatusCode::CONFLICT,
+                request_id: None,
+                retry_after: None,
+                client_message: Some(
+                    "The barcode is outdated, the customer has to show the current pass.",
+                ),
+            },
+            Error::BarcodeUnsigned => Self {
+                error_name: "BarcodeUnsigned",
+                error_details: Some("only signed barcodes are accepted".into()),
+                status: StatusCode::UNPROCESSABLE_ENTITY,
+                request_id: None,
+                retry_after: None,
+                client_message: Some(
+                    "The barcode is not signed, the customer has to show the pass in Wallet.",
+                ),
+            },
+            Error::EventNotFound => Self {
+                error_name: "EventNotFound",
+                error_details: Some("this event does not exist".into()),
+                status: StatusCode::NOT_FOUND,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("the event you search for does not exist."),
+            },
+            Error::TicketAlreadyCheckedIn { checked_in_at } => Self {
+                error_name: "TicketAlreadyCheckedIn",
+                error_details: Some(serde_json::json!({
+                    "checkedInAt": Utc.from_utc_datetime(&checked_in_at)
+                })),
+                status: StatusCode::CONFLICT,
+                request_id: None,
+                retry_after: None,
+                client_message: Some("The ticket was already used."),
+            },
             Error::AxumPathRejection(rejection) => {
                 Self {
                     error_name: "PathRejection",
                     request_id: None,
+                    retry_after: None,
                     status: rejection.status(),
                     client_message: None,
                     error_details: Some(rejection.body_text().into()),
@@ -104,6 +199,7 @@ impl From<Error> for ClientError {
                 Self {
                     error_name: "JsonRejection",
                     request_id: None,
+                    retry_after: None,
                     status: rejection.status(),
                     client_message: None,
                     error_details: Some(rejection.body_text().into()),
@@ -113,6 +209,7 @@ impl From<Error> for ClientError {
                 Self {
                     error_name: "HeaderRejection",
                     request_id: None,
+                    retry_after: None,
                     status: StatusCode::BAD_REQUEST,
                     client_message: None,
                     error_details: Some(rejection.to_string().into()),
@@ -127,6 +224,10 @@ impl IntoResponse for ClientError {
         let status = self.status;
         let mut res = axum::Json(self.clone()).into_response();
         *res.status_mut() = status;
+        if let Some(retry_after) = self.retry_after {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+        }
         res
     }
 }
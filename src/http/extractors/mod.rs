@@ -0,0 +1,3 @@
+mod pass_reference;
+
+pub use pass_reference::PassReference;
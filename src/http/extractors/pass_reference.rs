@@ -0,0 +1,31 @@
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+
+use crate::{error::Error, http::AppState};
+
+#[derive(serde::Deserialize)]
+struct PassReferencePathParams {
+    serial_number: String,
+}
+
+/// The serial number of the pass in the `{serial_number}` path parameter, which also accepts the
+/// member code from the back of the pass.
+pub struct PassReference(pub String);
+
+impl FromRequestParts<AppState> for PassReference {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Path(PassReferencePathParams { serial_number }) =
+            Path::from_request_parts(parts, state).await?;
+
+        Ok(Self(
+            state.app.resolve_pass_reference(&serial_number).await?,
+        ))
+    }
+}
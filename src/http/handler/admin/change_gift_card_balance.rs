@@ -0,0 +1,44 @@
+use axum::{extract::State, Extension, Json};
+
+use crate::{
+    http::{extractors::PassReference, AppState, OidcSub},
+    Result,
+};
+
+use super::GetGiftCardResponse;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeGiftCardBalanceJsonBody {
+    /// In minor currency units, e.g. cents.
+    pub amount: i64,
+    pub note: Option<String>,
+}
+
+pub async fn handle_top_up_gift_card(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    PassReference(serial_number): PassReference,
+    Json(body): Json<ChangeGiftCardBalanceJsonBody>,
+) -> Result<Json<GetGiftCardResponse>> {
+    let gift_card = state
+        .app
+        .top_up_gift_card(&serial_number, body.amount, body.note.as_deref(), &sub)
+        .await?;
+
+    Ok(Json(gift_card.into()))
+}
+
+pub async fn handle_spend_gift_card(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    PassReference(serial_number): PassReference,
+    Json(body): Json<ChangeGiftCardBalanceJsonBody>,
+) -> Result<Json<GetGiftCardResponse>> {
+    let gift_card = state
+        .app
+        .spend_gift_card(&serial_number, body.amount, body.note.as_deref(), &sub)
+        .await?;
+
+    Ok(Json(gift_card.into()))
+}
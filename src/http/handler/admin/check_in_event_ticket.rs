@@ -0,0 +1,22 @@
+use axum::{extract::State, Extension, Json};
+
+use crate::{
+    http::{extractors::PassReference, AppState, OidcSub},
+    Result,
+};
+
+use super::GetEventTicketResponse;
+
+/// Marks the ticket as used, a second check-in fails with a conflict.
+pub async fn handle_check_in_event_ticket(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetEventTicketResponse>> {
+    let ticket = state
+        .app
+        .check_in_event_ticket(&serial_number, &sub)
+        .await?;
+
+    Ok(Json(ticket.into()))
+}
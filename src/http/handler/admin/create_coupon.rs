@@ -0,0 +1,62 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    app::NewCoupon,
+    http::{AppState, OidcSub},
+    Result,
+};
+
+use super::{GetCouponResponse, PassDownloadLink};
+
+fn default_max_redemptions() -> i32 {
+    1
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCouponJsonBody {
+    pub discount_text: String,
+    pub details: Option<String>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    #[serde(default = "default_max_redemptions")]
+    pub max_redemptions: i32,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCouponResponse {
+    pub coupon: GetCouponResponse,
+    #[serde(flatten)]
+    pub download_link: PassDownloadLink,
+}
+
+/// Issues a coupon and returns a link to hand it to the customer.
+pub async fn handle_create_coupon(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    Json(body): Json<CreateCouponJsonBody>,
+) -> Result<(StatusCode, Json<CreateCouponResponse>)> {
+    let serial_number = state
+        .app
+        .add_coupon_pass(NewCoupon {
+            discount_text: body.discount_text,
+            details: body.details,
+            valid_from: body.valid_from,
+            valid_until: body.valid_until,
+            max_redemptions: body.max_redemptions,
+        })
+        .await?;
+
+    let download_link = PassDownloadLink::create(&state, &serial_number, &sub).await?;
+    let coupon = state.app.get_coupon_pass(&serial_number).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateCouponResponse {
+            coupon: coupon.into(),
+            download_link,
+        }),
+    ))
+}
@@ -0,0 +1,43 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    app::NewEvent,
+    http::{AppState, OidcSub},
+    Result,
+};
+
+use super::GetEventResponse;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEventJsonBody {
+    pub name: String,
+    pub venue: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+pub async fn handle_create_event(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    Json(body): Json<CreateEventJsonBody>,
+) -> Result<(StatusCode, Json<GetEventResponse>)> {
+    let event = state
+        .app
+        .add_event(
+            NewEvent {
+                name: body.name,
+                venue: body.venue,
+                starts_at: body.starts_at,
+                ends_at: body.ends_at,
+            },
+            &sub,
+        )
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(GetEventResponse::new(event, 0, 0)),
+    ))
+}
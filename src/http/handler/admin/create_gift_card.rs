@@ -0,0 +1,58 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+
+use crate::{
+    app::NewGiftCard,
+    http::{AppState, OidcSub},
+    Result,
+};
+
+use super::{GetGiftCardResponse, PassDownloadLink};
+
+fn default_currency() -> String {
+    "EUR".into()
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGiftCardJsonBody {
+    /// In minor currency units, e.g. cents.
+    pub initial_balance: i64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    pub pass_holder_name: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGiftCardResponse {
+    pub gift_card: GetGiftCardResponse,
+    #[serde(flatten)]
+    pub download_link: PassDownloadLink,
+}
+
+/// Issues a gift card and returns a link to hand it to the customer.
+pub async fn handle_create_gift_card(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    Json(body): Json<CreateGiftCardJsonBody>,
+) -> Result<(StatusCode, Json<CreateGiftCardResponse>)> {
+    let serial_number = state
+        .app
+        .add_gift_card_pass(NewGiftCard {
+            initial_balance: body.initial_balance,
+            currency: body.currency,
+            pass_holder_name: body.pass_holder_name,
+        })
+        .await?;
+
+    let download_link = PassDownloadLink::create(&state, &serial_number, &sub).await?;
+    let gift_card = state.app.get_gift_card_pass(&serial_number).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateGiftCardResponse {
+            gift_card: gift_card.into(),
+            download_link,
+        }),
+    ))
+}
@@ -0,0 +1,47 @@
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::{app::PassEmail, http::AppState, Error, Result};
+
+use super::GetLoyalityPassResponse;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLoyalityPassJsonBody {
+    pub pass_holder_name: String,
+    /// The pass is sent to this address.
+    pub email: String,
+    /// Selects the email template.
+    pub store: Option<String>,
+    /// Selects the email template, e.g. `de` or `en-GB`.
+    pub locale: Option<String>,
+}
+
+/// Creates a loyality pass and sends it to the customer by email. Only staff can send emails,
+/// the public pass creation doesn't take an address.
+pub async fn handle_create_loyality_pass(
+    State(state): State<AppState>,
+    Json(body): Json<CreateLoyalityPassJsonBody>,
+) -> Result<(StatusCode, Json<GetLoyalityPassResponse>)> {
+    let pass_holder_name = body.pass_holder_name.trim();
+    if pass_holder_name.is_empty() {
+        return Err(Error::InvalidRequest(
+            "passHolderName must not be empty".into(),
+        ));
+    }
+
+    let (_, serial_number) = state
+        .app
+        .add_pass(
+            pass_holder_name,
+            Some(PassEmail {
+                to: body.email,
+                store: body.store,
+                locale: body.locale,
+            }),
+        )
+        .await?;
+
+    let loyality_pass = state.app.get_loyality_pass(&serial_number).await?;
+
+    Ok((StatusCode::CREATED, Json(loyality_pass.into())))
+}
@@ -0,0 +1,53 @@
+use axum::{extract::State, Extension, Json};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    app::PassRecoveryOptions,
+    http::{extractors::PassReference, AppState, OidcSub},
+    Result,
+};
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePassRecoveryJsonBody {
+    #[serde(default)]
+    pub rotate_auth_token: bool,
+    #[serde(default)]
+    pub invalidate_registrations: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePassRecoveryResponse {
+    pub recovery_path: String,
+    /// Only set if the public url of the server is configured.
+    pub recovery_url: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn handle_create_pass_recovery(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    PassReference(serial_number): PassReference,
+    Json(body): Json<CreatePassRecoveryJsonBody>,
+) -> Result<Json<CreatePassRecoveryResponse>> {
+    let (recovery_token, expires_at) = state
+        .app
+        .create_pass_recovery(
+            &serial_number,
+            PassRecoveryOptions {
+                rotate_auth_token: body.rotate_auth_token,
+                invalidate_registrations: body.invalidate_registrations,
+            },
+            &sub,
+        )
+        .await?;
+
+    let recovery_path = format!("/recovery/{recovery_token}");
+
+    Ok(Json(CreatePassRecoveryResponse {
+        recovery_url: state.public_link(&recovery_path),
+        recovery_path,
+        expires_at,
+    }))
+}
@@ -0,0 +1,45 @@
+use axum::{extract::State, Json};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    db::DbPassTypeCoupon,
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCouponResponse {
+    pub serial_number: String,
+    pub discount_text: String,
+    pub details: Option<String>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub max_redemptions: i32,
+    pub redemptions: i32,
+    pub last_redeemed_at: Option<DateTime<Utc>>,
+}
+
+impl From<DbPassTypeCoupon> for GetCouponResponse {
+    fn from(coupon: DbPassTypeCoupon) -> Self {
+        Self {
+            serial_number: coupon.serial_number,
+            discount_text: coupon.discount_text,
+            details: coupon.details,
+            valid_from: coupon.valid_from.map(|d| Utc.from_utc_datetime(&d)),
+            valid_until: coupon.valid_until.map(|d| Utc.from_utc_datetime(&d)),
+            max_redemptions: coupon.max_redemptions,
+            redemptions: coupon.redemptions,
+            last_redeemed_at: coupon.last_redeemed_at.map(|d| Utc.from_utc_datetime(&d)),
+        }
+    }
+}
+
+pub async fn handle_get_coupon(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetCouponResponse>> {
+    let coupon = state.app.get_coupon_pass(&serial_number).await?;
+
+    Ok(Json(coupon.into()))
+}
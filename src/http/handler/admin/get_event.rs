@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{db::DbEvent, http::AppState, Result};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventResponse {
+    pub id: String,
+    pub name: String,
+    pub venue: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+    pub issued_tickets: i64,
+    pub checked_in_tickets: i64,
+}
+
+impl GetEventResponse {
+    pub fn new(event: DbEvent, issued_tickets: i64, checked_in_tickets: i64) -> Self {
+        Self {
+            id: event.id,
+            name: event.name,
+            venue: event.venue,
+            starts_at: Utc.from_utc_datetime(&event.starts_at),
+            ends_at: event.ends_at.map(|d| Utc.from_utc_datetime(&d)),
+            created_at: Utc.from_utc_datetime(&event.created_at),
+            created_by: event.created_by,
+            issued_tickets,
+            checked_in_tickets,
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct GetEventPathParams {
+    pub event_id: String,
+}
+
+pub async fn handle_get_event(
+    State(state): State<AppState>,
+    Path(GetEventPathParams { event_id }): Path<GetEventPathParams>,
+) -> Result<Json<GetEventResponse>> {
+    let event = state.app.get_event(&event_id).await?;
+    let (issued_tickets, checked_in_tickets) = state.app.event_ticket_counts(&event_id).await?;
+
+    Ok(Json(GetEventResponse::new(
+        event,
+        issued_tickets,
+        checked_in_tickets,
+    )))
+}
@@ -0,0 +1,39 @@
+use axum::{extract::State, Json};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    db::DbPassTypeEventTicket,
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventTicketResponse {
+    pub serial_number: String,
+    pub event_id: String,
+    pub attendee_name: Option<String>,
+    pub seat: Option<String>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+}
+
+impl From<DbPassTypeEventTicket> for GetEventTicketResponse {
+    fn from(ticket: DbPassTypeEventTicket) -> Self {
+        Self {
+            serial_number: ticket.serial_number,
+            event_id: ticket.event_id,
+            attendee_name: ticket.attendee_name,
+            seat: ticket.seat,
+            checked_in_at: ticket.checked_in_at.map(|d| Utc.from_utc_datetime(&d)),
+        }
+    }
+}
+
+pub async fn handle_get_event_ticket(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetEventTicketResponse>> {
+    let ticket = state.app.get_event_ticket_pass(&serial_number).await?;
+
+    Ok(Json(ticket.into()))
+}
@@ -0,0 +1,40 @@
+use axum::{extract::State, Json};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    db::DbPassTypeGiftCard,
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGiftCardResponse {
+    pub serial_number: String,
+    /// In minor currency units, e.g. cents.
+    pub balance: i64,
+    pub currency: String,
+    pub pass_holder_name: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<DbPassTypeGiftCard> for GetGiftCardResponse {
+    fn from(gift_card: DbPassTypeGiftCard) -> Self {
+        Self {
+            serial_number: gift_card.serial_number,
+            balance: gift_card.balance,
+            currency: gift_card.currency,
+            pass_holder_name: gift_card.pass_holder_name,
+            last_used_at: gift_card.last_used_at.map(|d| Utc.from_utc_datetime(&d)),
+        }
+    }
+}
+
+pub async fn handle_get_gift_card(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetGiftCardResponse>> {
+    let gift_card = state.app.get_gift_card_pass(&serial_number).await?;
+
+    Ok(Json(gift_card.into()))
+}
@@ -1,10 +1,11 @@
-use axum::{
-    extract::{Path, State},
-    Json,
-};
+use axum::{extract::State, Json};
 use chrono::{DateTime, TimeZone, Utc};
 
-use crate::{http::AppState, Result};
+use crate::{
+    db::DbPassTypeLoyality,
+    http::{extractors::PassReference, AppState},
+    Result,
+};
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,28 +15,41 @@ pub struct GetLoyalityPassResponse {
     pub total_points: i32,
     pub current_points: i32,
     pub pass_holder_name: String,
+    pub pass_holder_email: Option<String>,
+    pub pass_holder_phone: Option<String>,
     pub last_used_at: Option<DateTime<Utc>>,
+    pub lifetime_points: i32,
+    pub tier: Option<String>,
+    pub tier_reached_at: Option<DateTime<Utc>>,
 }
 
-#[derive(serde::Deserialize)]
-pub struct GetLoyalityPassPathParams {
-    pub serial_number: String,
+impl From<DbPassTypeLoyality> for GetLoyalityPassResponse {
+    fn from(loyality_pass: DbPassTypeLoyality) -> Self {
+        Self {
+            serial_number: loyality_pass.serial_number,
+            already_redeemed: loyality_pass.already_redeemed,
+            total_points: loyality_pass.total_points,
+            current_points: loyality_pass.current_points,
+            pass_holder_name: loyality_pass.pass_holder_name,
+            pass_holder_email: loyality_pass.pass_holder_email,
+            pass_holder_phone: loyality_pass.pass_holder_phone,
+            last_used_at: loyality_pass
+                .last_used_at
+                .map(|d| Utc.from_utc_datetime(&d)),
+            lifetime_points: loyality_pass.lifetime_points,
+            tier: loyality_pass.tier,
+            tier_reached_at: loyality_pass
+                .tier_reached_at
+                .map(|d| Utc.from_utc_datetime(&d)),
+        }
+    }
 }
 
 pub async fn handle_get_loyality_pass(
     State(state): State<AppState>,
-    Path(GetLoyalityPassPathParams { serial_number }): Path<GetLoyalityPassPathParams>,
+    PassReference(serial_number): PassReference,
 ) -> Result<Json<GetLoyalityPassResponse>> {
     let loyality_pass = state.app.get_loyality_pass(&serial_number).await?;
 
-    Ok(Json(GetLoyalityPassResponse {
-        serial_number: loyality_pass.serial_number,
-        already_redeemed: loyality_pass.already_redeemed,
-        total_points: loyality_pass.total_points,
-        current_points: loyality_pass.current_points,
-        pass_holder_name: loyality_pass.pass_holder_name,
-        last_used_at: loyality_pass
-            .last_used_at
-            .map(|d| Utc.from_utc_datetime(&d)),
-    }))
+    Ok(Json(loyality_pass.into()))
 }
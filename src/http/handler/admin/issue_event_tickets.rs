@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::{
+    app::NewEventTicket,
+    http::{AppState, OidcSub},
+    Result,
+};
+
+use super::{GetEventTicketResponse, PassDownloadLink};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueEventTicketJsonBody {
+    pub attendee_name: Option<String>,
+    /// Seat or time slot of the attendee.
+    pub seat: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct IssueEventTicketsJsonBody {
+    pub tickets: Vec<IssueEventTicketJsonBody>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct IssueEventTicketsPathParams {
+    pub event_id: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedEventTicketResponse {
+    pub ticket: GetEventTicketResponse,
+    #[serde(flatten)]
+    pub download_link: PassDownloadLink,
+}
+
+#[derive(serde::Serialize)]
+pub struct IssueEventTicketsResponse {
+    pub tickets: Vec<IssuedEventTicketResponse>,
+}
+
+/// Issues tickets for all attendees of an event at once and returns a download link for each.
+pub async fn handle_issue_event_tickets(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    Path(IssueEventTicketsPathParams { event_id }): Path<IssueEventTicketsPathParams>,
+    Json(body): Json<IssueEventTicketsJsonBody>,
+) -> Result<(StatusCode, Json<IssueEventTicketsResponse>)> {
+    let issued = state
+        .app
+        .issue_event_tickets(
+            &event_id,
+            body.tickets
+                .into_iter()
+                .map(|t| NewEventTicket {
+                    attendee_name: t.attendee_name,
+                    seat: t.seat,
+                })
+                .collect(),
+            &sub,
+        )
+        .await?;
+
+    let tickets = issued
+        .into_iter()
+        .map(|issued| IssuedEventTicketResponse {
+            download_link: PassDownloadLink::new(
+                &state,
+                issued.download_path,
+                issued.download_expires_at,
+            ),
+            ticket: issued.ticket.into(),
+        })
+        .collect();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(IssueEventTicketsResponse { tickets }),
+    ))
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    db::{DbDeviceLog, DbDeviceLogFilter},
+    http::AppState,
+    Error, Result,
+};
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 200;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeviceLogsQueryParams {
+    pub device_library_id: Option<String>,
+    pub serial_number: Option<String>,
+    pub seen_after: Option<DateTime<Utc>>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLogResponse {
+    pub message: String,
+    pub device_library_id: Option<String>,
+    pub pass_type_id: Option<String>,
+    pub serial_number: Option<String>,
+    pub first_logged_at: Option<DateTime<Utc>>,
+    pub last_logged_at: Option<DateTime<Utc>>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub occurrences: i32,
+}
+
+impl From<DbDeviceLog> for DeviceLogResponse {
+    fn from(value: DbDeviceLog) -> Self {
+        Self {
+            message: value.message,
+            device_library_id: value.device_library_id,
+            pass_type_id: value.pass_type_id,
+            serial_number: value.pass_serial_number,
+            first_logged_at: value.first_logged_at.map(|d| Utc.from_utc_datetime(&d)),
+            last_logged_at: value.last_logged_at.map(|d| Utc.from_utc_datetime(&d)),
+            first_seen_at: Utc.from_utc_datetime(&value.first_seen_at),
+            last_seen_at: Utc.from_utc_datetime(&value.last_seen_at),
+            occurrences: value.occurrences,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeviceLogsResponse {
+    pub logs: Vec<DeviceLogResponse>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+}
+
+pub async fn handle_list_device_logs(
+    State(state): State<AppState>,
+    Query(params): Query<ListDeviceLogsQueryParams>,
+) -> Result<Json<ListDeviceLogsResponse>> {
+    let page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+
+    if page == 0 {
+        return Err(Error::InvalidRequest("page starts at 1".into()));
+    }
+
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(Error::InvalidRequest(format!(
+            "perPage must be between 1 and {MAX_PER_PAGE}"
+        )));
+    }
+
+    let filter = DbDeviceLogFilter {
+        device_library_id: params.device_library_id,
+        pass_serial_number: params.serial_number,
+        seen_after: params.seen_after.map(|d| d.naive_utc()),
+    };
+
+    let (logs, total) = state
+        .app
+        .search_device_logs(
+            &filter,
+            per_page.into(),
+            i64::from(page - 1) * i64::from(per_page),
+        )
+        .await?;
+
+    Ok(Json(ListDeviceLogsResponse {
+        logs: logs.into_iter().map(Into::into).collect(),
+        page,
+        per_page,
+        total,
+    }))
+}
@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    db::DbGiftCardTransaction,
+    http::{extractors::PassReference, AppState},
+    Error, Result,
+};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListGiftCardTransactionsQueryParams {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiftCardTransactionResponse {
+    pub id: i64,
+    /// Positive for top-ups, negative for spendings.
+    pub amount: i64,
+    pub balance_after: i64,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Option<String>,
+}
+
+impl From<DbGiftCardTransaction> for GiftCardTransactionResponse {
+    fn from(transaction: DbGiftCardTransaction) -> Self {
+        Self {
+            id: transaction.id,
+            amount: transaction.amount,
+            balance_after: transaction.balance_after,
+            note: transaction.note,
+            created_at: Utc.from_utc_datetime(&transaction.created_at),
+            created_by: transaction.created_by,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListGiftCardTransactionsResponse {
+    pub transactions: Vec<GiftCardTransactionResponse>,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+pub async fn handle_list_gift_card_transactions(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+    Query(params): Query<ListGiftCardTransactionsQueryParams>,
+) -> Result<Json<ListGiftCardTransactionsResponse>> {
+    let page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+
+    if page == 0 {
+        return Err(Error::InvalidRequest("page starts at 1".into()));
+    }
+
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(Error::InvalidRequest(format!(
+            "perPage must be between 1 and {MAX_PER_PAGE}"
+        )));
+    }
+
+    let transactions = state
+        .app
+        .gift_card_transactions(
+            &serial_number,
+            per_page.into(),
+            i64::from(page - 1) * i64::from(per_page),
+        )
+        .await?;
+
+    Ok(Json(ListGiftCardTransactionsResponse {
+        transactions: transactions.into_iter().map(Into::into).collect(),
+        page,
+        per_page,
+    }))
+}
@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    db::{DbLoyalityPassSearch, DbLoyalityPassSortBy},
+    http::AppState,
+    Error, Result,
+};
+
+use super::GetLoyalityPassResponse;
+
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(serde::Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SortBy {
+    #[default]
+    Relevance,
+    CreatedAt,
+    LastUsedAt,
+    PassHolderName,
+    CurrentPoints,
+    AlreadyRedeemed,
+}
+
+impl From<SortBy> for DbLoyalityPassSortBy {
+    fn from(value: SortBy) -> Self {
+        match value {
+            SortBy::Relevance => Self::Relevance,
+            SortBy::CreatedAt => Self::CreatedAt,
+            SortBy::LastUsedAt => Self::LastUsedAt,
+            SortBy::PassHolderName => Self::PassHolderName,
+            SortBy::CurrentPoints => Self::CurrentPoints,
+            SortBy::AlreadyRedeemed => Self::AlreadyRedeemed,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLoyalityPassesQueryParams {
+    pub q: Option<String>,
+    pub pass_holder_name: Option<String>,
+    pub pass_holder_email: Option<String>,
+    pub pass_holder_phone: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub min_points: Option<i32>,
+    pub max_points: Option<i32>,
+    #[serde(default)]
+    pub sort_by: SortBy,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLoyalityPassesResponse {
+    pub passes: Vec<GetLoyalityPassResponse>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+}
+
+pub async fn handle_list_loyality_passes(
+    State(state): State<AppState>,
+    Query(params): Query<ListLoyalityPassesQueryParams>,
+) -> Result<Json<ListLoyalityPassesResponse>> {
+    let page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+
+    if page == 0 {
+        return Err(Error::InvalidRequest("page starts at 1".into()));
+    }
+
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(Error::InvalidRequest(format!(
+            "perPage must be between 1 and {MAX_PER_PAGE}"
+        )));
+    }
+
+    let search = DbLoyalityPassSearch {
+        query: params.q.filter(|q| !q.trim().is_empty()),
+        pass_holder_name: params.pass_holder_name,
+        pass_holder_email: params.pass_holder_email,
+        pass_holder_phone: params.pass_holder_phone,
+        created_after: params.created_after.map(|d| d.naive_utc()),
+        created_before: params.created_before.map(|d| d.naive_utc()),
+        min_points: params.min_points,
+        max_points: params.max_points,
+        sort_by: params.sort_by.into(),
+        descending: matches!(params.order, SortOrder::Desc),
+        limit: per_page.into(),
+        offset: i64::from(page - 1) * i64::from(per_page),
+    };
+
+    let (passes, total) = state.app.search_loyality_passes(&search).await?;
+
+    Ok(Json(ListLoyalityPassesResponse {
+        passes: passes.into_iter().map(Into::into).collect(),
+        page,
+        per_page,
+        total,
+    }))
+}
@@ -1,7 +1,12 @@
-use axum::extract::{Path, State};
+use axum::extract::State;
 use axum::Json;
 
-use crate::{http::AppState, Result};
+use crate::{
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+use super::GetLoyalityPassResponse;
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -9,20 +14,15 @@ pub struct JsonBody {
     add_points: u16,
 }
 
-#[derive(serde::Deserialize)]
-pub struct AddPointsToLoyalityCardPathParams {
-    pub serial_number: String,
-}
-
 pub async fn handle_add_points_to_loyality_card(
     State(state): State<AppState>,
-    Path(AddPointsToLoyalityCardPathParams { serial_number }): Path<
-        AddPointsToLoyalityCardPathParams,
-    >,
+    PassReference(serial_number): PassReference,
     Json(JsonBody { add_points }): Json<JsonBody>,
-) -> Result<()> {
-    state
+) -> Result<Json<GetLoyalityPassResponse>> {
+    let loyality_pass = state
         .app
         .pass_loyality_add_points(&serial_number, add_points.into())
-        .await
+        .await?;
+
+    Ok(Json(loyality_pass.into()))
 }
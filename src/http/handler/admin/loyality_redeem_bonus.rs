@@ -1,17 +1,18 @@
-use axum::extract::{Path, State};
+use axum::extract::State;
+use axum::Json;
 
-use crate::{http::AppState, Result};
+use crate::{
+    http::{extractors::PassReference, AppState},
+    Result,
+};
 
-#[derive(serde::Deserialize)]
-pub struct LoyalityCardRedeemBonusPathParams {
-    pub serial_number: String,
-}
+use super::GetLoyalityPassResponse;
 
 pub async fn handle_loyality_card_redeem_bonus(
     State(state): State<AppState>,
-    Path(LoyalityCardRedeemBonusPathParams { serial_number }): Path<
-        LoyalityCardRedeemBonusPathParams,
-    >,
-) -> Result<()> {
-    state.app.pass_loyality_redeem_bonus(&serial_number).await
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetLoyalityPassResponse>> {
+    let loyality_pass = state.app.pass_loyality_redeem_bonus(&serial_number).await?;
+
+    Ok(Json(loyality_pass.into()))
 }
@@ -1,7 +1,47 @@
+mod change_gift_card_balance;
+mod check_in_event_ticket;
+mod create_coupon;
+mod create_event;
+mod create_gift_card;
+mod create_loyality_pass;
+mod create_pass_recovery;
+mod get_coupon;
+mod get_event;
+mod get_event_ticket;
+mod get_gift_card;
 mod get_loyality_card;
+mod issue_event_tickets;
+mod list_device_logs;
+mod list_gift_card_transactions;
+mod list_loyality_passes;
 mod loyality_add_points;
 mod loyality_redeem_bonus;
+mod pass_download_link;
+mod redeem_coupon;
+mod rotate_auth_tokens;
+mod scan_pass;
+mod update_pass_holder;
 
+pub use change_gift_card_balance::*;
+pub use check_in_event_ticket::*;
+pub use create_coupon::*;
+pub use create_event::*;
+pub use create_gift_card::*;
+pub use create_loyality_pass::*;
+pub use create_pass_recovery::*;
+pub use get_coupon::*;
+pub use get_event::*;
+pub use get_event_ticket::*;
+pub use get_gift_card::*;
 pub use get_loyality_card::*;
+pub use issue_event_tickets::*;
+pub use list_device_logs::*;
+pub use list_gift_card_transactions::*;
+pub use list_loyality_passes::*;
 pub use loyality_add_points::*;
 pub use loyality_redeem_bonus::*;
+pub use pass_download_link::*;
+pub use redeem_coupon::*;
+pub use rotate_auth_tokens::*;
+pub use scan_pass::*;
+pub use update_pass_holder::*;
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+
+use crate::{app::PassRecoveryOptions, http::AppState, Result};
+
+/// A link with which a newly issued pass can be added to Wallet by the customer.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PassDownloadLink {
+    pub download_path: String,
+    /// Only set if the public url of the server is configured.
+    pub download_url: Option<String>,
+    pub download_expires_at: DateTime<Utc>,
+}
+
+impl PassDownloadLink {
+    pub async fn create(state: &AppState, serial_number: &str, created_by: &str) -> Result<Self> {
+        let (recovery_token, download_expires_at) = state
+            .app
+            .create_pass_recovery(
+                serial_number,
+                PassRecoveryOptions {
+                    rotate_auth_token: false,
+                    invalidate_registrations: false,
+                },
+                created_by,
+            )
+            .await?;
+
+        Ok(Self::new(
+            state,
+            format!("/recovery/{recovery_token}"),
+            download_expires_at,
+        ))
+    }
+
+    pub fn new(
+        state: &AppState,
+        download_path: String,
+        download_expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            download_url: state.public_link(&download_path),
+            download_path,
+            download_expires_at,
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+use super::GetCouponResponse;
+
+pub async fn handle_redeem_coupon(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+) -> Result<Json<GetCouponResponse>> {
+    let coupon = state.app.redeem_coupon_pass(&serial_number).await?;
+
+    Ok(Json(coupon.into()))
+}
@@ -0,0 +1,15 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::{
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+pub async fn handle_rotate_auth_tokens(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+) -> Result<StatusCode> {
+    state.app.rotate_pass_auth_tokens(&serial_number).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,86 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    app::{Scan, ScanAction},
+    db::DbPassType,
+    http::{AppState, OidcSub},
+    wallet::PassKind,
+    Result,
+};
+
+use super::{
+    GetCouponResponse, GetEventTicketResponse, GetGiftCardResponse, GetLoyalityPassResponse,
+};
+
+#[derive(serde::Deserialize)]
+pub struct ScanPassJsonBody {
+    /// The scanned text of the barcode: a signed barcode, a member code or a serial number.
+    pub payload: String,
+    /// The store the scanner is in.
+    pub store: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+pub enum ScannedPass {
+    Loyality(GetLoyalityPassResponse),
+    Coupon(GetCouponResponse),
+    GiftCard(GetGiftCardResponse),
+    EventTicket(GetEventTicketResponse),
+}
+
+impl From<DbPassType> for ScannedPass {
+    fn from(pass_type: DbPassType) -> Self {
+        match pass_type {
+            DbPassType::Loyality(l) => Self::Loyality(l.into()),
+            DbPassType::Coupon(c) => Self::Coupon(c.into()),
+            DbPassType::GiftCard(g) => Self::GiftCard(g.into()),
+            DbPassType::EventTicket(e) => Self::EventTicket(e.into()),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanPassResponse {
+    pub scan_id: i64,
+    pub scanned_at: DateTime<Utc>,
+    pub serial_number: String,
+    pub member_code: String,
+    pub kind: PassKind,
+    /// Whether the barcode was signed, unsigned barcodes could be copied from another pass.
+    pub signed: bool,
+    pub pass: ScannedPass,
+    pub actions: Vec<ScanAction>,
+}
+
+impl From<Scan> for ScanPassResponse {
+    fn from(scan: Scan) -> Self {
+        Self {
+            scan_id: scan.event.id,
+            scanned_at: Utc.from_utc_datetime(&scan.event.scanned_at),
+            serial_number: scan.barcode.pass.serial_number,
+            member_code: scan.barcode.pass.member_code,
+            kind: scan.barcode.kind,
+            signed: scan.barcode.signed,
+            pass: scan.pass_type.into(),
+            actions: scan.actions,
+        }
+    }
+}
+
+/// Resolves a scanned barcode to its pass, records the scan and returns the actions the staff
+/// can take with the pass.
+pub async fn handle_scan_pass(
+    State(state): State<AppState>,
+    Extension(sub): Extension<OidcSub>,
+    Json(body): Json<ScanPassJsonBody>,
+) -> Result<(StatusCode, Json<ScanPassResponse>)> {
+    let scan = state
+        .app
+        .scan_pass(&body.payload, body.store.as_deref(), &sub)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(scan.into())))
+}
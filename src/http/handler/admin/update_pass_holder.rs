@@ -0,0 +1,42 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::{
+    db::DbPassHolderUpdate,
+    http::{extractors::PassReference, AppState},
+    Result,
+};
+
+use super::GetLoyalityPassResponse;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePassHolderJsonBody {
+    pub pass_holder_name: Option<String>,
+    /// `null` removes the email, a missing field keeps it.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub pass_holder_email: Option<Option<String>>,
+    /// `null` removes the phone number, a missing field keeps it.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub pass_holder_phone: Option<Option<String>>,
+}
+
+pub async fn handle_update_pass_holder(
+    State(state): State<AppState>,
+    PassReference(serial_number): PassReference,
+    Json(body): Json<UpdatePassHolderJsonBody>,
+) -> Result<Json<GetLoyalityPassResponse>> {
+    let loyality_pass = state
+        .app
+        .update_loyality_pass_holder(
+            &serial_number,
+            DbPassHolderUpdate {
+                pass_holder_name: body.pass_holder_name,
+                pass_holder_email: body.pass_holder_email,
+                pass_holder_phone: body.pass_holder_phone,
+            },
+        )
+        .await?;
+
+    Ok(Json(loyality_pass.into()))
+}
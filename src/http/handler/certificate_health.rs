@@ -0,0 +1,50 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+
+use crate::http::AppState;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateHealth {
+    pub pass_type_id: String,
+    pub certificate: &'static str,
+    pub not_after: DateTime<Utc>,
+    pub days_until_expiry: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateHealthResponse {
+    pub certificates: Vec<CertificateHealth>,
+}
+
+/// Lists the expiry of all loaded certificates, responds with 503 if any of them expired.
+pub async fn handle_certificate_health(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<CertificateHealthResponse>) {
+    let now = Utc::now();
+
+    let certificates = state
+        .app
+        .pass_types()
+        .iter()
+        .flat_map(|pass_type| {
+            pass_type
+                .certificates
+                .iter()
+                .map(|certificate| CertificateHealth {
+                    pass_type_id: pass_type.pass_type_identifier.clone(),
+                    certificate: certificate.name,
+                    not_after: certificate.not_after,
+                    days_until_expiry: (certificate.not_after - now).num_days(),
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let status = match certificates.iter().any(|c| c.not_after <= now) {
+        true => StatusCode::SERVICE_UNAVAILABLE,
+        false => StatusCode::OK,
+    };
+
+    (status, Json(CertificateHealthResponse { certificates }))
+}
@@ -12,7 +12,7 @@ use crate::{http::AppState, wallet::body_from_package, Result};
 pub async fn handle_create_pass(
     state: State<AppState>,
 ) -> Result<([(HeaderName, String); 3], Body)> {
-    let (mut wallet_pass, serial_number) = state.app.add_pass("Test Name").await?;
+    let (mut wallet_pass, serial_number) = state.app.add_pass("Test Name", None).await?;
 
     let body = body_from_package(&mut wallet_pass)?;
 
@@ -0,0 +1,453 @@
+use std::fmt::Write;
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderName},
+    response::Html,
+    Json,
+};
+use chrono::{DateTime, Datelike, Days, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::{
+    app::{CustomerHistoryEntry, CustomerView},
+    db::DbPassType,
+    http::AppState,
+    member_code,
+    wallet::{body_from_package, format_money, PassKind},
+    Result,
+};
+
+#[derive(serde::Deserialize)]
+pub struct CustomerViewPathParams {
+    pub serial_number: String,
+    pub token: String,
+}
+
+/// What the customer sees of a pass, without the internal details shown to the staff.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum CustomerPassDetails {
+    #[serde(rename_all = "camelCase")]
+    Loyality {
+        pass_holder_name: String,
+        current_points: i32,
+        total_points: i32,
+        already_redeemed: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Coupon {
+        discount_text: String,
+        details: Option<String>,
+        valid_from: Option<DateTime<Utc>>,
+        valid_until: Option<DateTime<Utc>>,
+        remaining_redemptions: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    GiftCard {
+        pass_holder_name: Option<String>,
+        balance: i64,
+        currency: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    EventTicket {
+        attendee_name: Option<String>,
+        seat: Option<String>,
+        checked_in_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl From<DbPassType> for CustomerPassDetails {
+    fn from(pass_type: DbPassType) -> Self {
+        let utc = |t: NaiveDateTime| Utc.from_utc_datetime(&t);
+
+        match pass_type {
+            DbPassType::Loyality(l) => Self::Loyality {
+                pass_holder_name: l.pass_holder_name,
+                current_points: l.current_points,
+                total_points: l.total_points,
+                already_redeemed: l.already_redeemed,
+            },
+            DbPassType::Coupon(c) => Self::Coupon {
+                remaining_redemptions: (c.max_redemptions - c.redemptions).max(0),
+                discount_text: c.discount_text,
+                details: c.details,
+                valid_from: c.valid_from.map(utc),
+                valid_until: c.valid_until.map(utc),
+            },
+            DbPassType::GiftCard(g) => Self::GiftCard {
+                pass_holder_name: g.pass_holder_name,
+                balance: g.balance,
+                currency: g.currency,
+            },
+            DbPassType::EventTicket(e) => Self::EventTicket {
+                attendee_name: e.attendee_name,
+                seat: e.seat,
+                checked_in_at: e.checked_in_at.map(utc),
+            },
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum CustomerHistoryEntryResponse {
+    #[serde(rename_all = "camelCase")]
+    Visit {
+        at: DateTime<Utc>,
+        store: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    GiftCardTransaction {
+        at: DateTime<Utc>,
+        amount: i64,
+        balance_after: i64,
+    },
+}
+
+impl From<CustomerHistoryEntry> for CustomerHistoryEntryResponse {
+    fn from(entry: CustomerHistoryEntry) -> Self {
+        match entry {
+            CustomerHistoryEntry::Visit { at, store } => Self::Visit {
+                at: Utc.from_utc_datetime(&at),
+                store,
+            },
+            CustomerHistoryEntry::GiftCardTransaction {
+                at,
+                amount,
+                balance_after,
+            } => Self::GiftCardTransaction {
+                at: Utc.from_utc_datetime(&at),
+                amount,
+                balance_after,
+            },
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerViewResponse {
+    pub serial_number: String,
+    pub member_code: String,
+    pub kind: PassKind,
+    pub last_updated_at: DateTime<Utc>,
+    pub pass: CustomerPassDetails,
+    /// Newest first.
+    pub history: Vec<CustomerHistoryEntryResponse>,
+}
+
+impl From<CustomerView> for CustomerViewResponse {
+    fn from(view: CustomerView) -> Self {
+        Self {
+            serial_number: view.pass.serial_number,
+            member_code: member_code::display(&view.pass.member_code),
+            kind: view.kind,
+            last_updated_at: Utc.from_utc_datetime(&view.pass.last_updated_at),
+            pass: view.pass_type.into(),
+            history: view.history.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// The pass and its history as JSON, for the customer.
+pub async fn handle_get_customer_view_data(
+    State(state): State<AppState>,
+    Path(CustomerViewPathParams {
+        serial_number,
+        token,
+    }): Path<CustomerViewPathParams>,
+) -> Result<Json<CustomerViewResponse>> {
+    let view = state.app.customer_view(&serial_number, &token).await?;
+
+    Ok(Json(view.into()))
+}
+
+/// The pass and its history as a web page, for customers without Wallet.
+pub async fn handle_get_customer_view(
+    State(state): State<AppState>,
+    Path(CustomerViewPathParams {
+        serial_number,
+        token,
+    }): Path<CustomerViewPathParams>,
+) -> Result<Html<String>> {
+    let view = state.app.customer_view(&serial_number, &token).await?;
+
+    Ok(Html(render_customer_view(&view.into(), &token)))
+}
+
+/// Adds the pass to Wallet again, e.g. after it was deleted.
+pub async fn handle_download_customer_pass(
+    State(state): State<AppState>,
+    Path(CustomerViewPathParams {
+        serial_number,
+        token,
+    }): Path<CustomerViewPathParams>,
+) -> Result<([(HeaderName, String); 3], Body)> {
+    let (mut wallet_pass, last_updated_at) = state
+        .app
+        .customer_pass_package(&serial_number, &token)
+        .await?;
+
+    let body = body_from_package(&mut wallet_pass)?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/vnd.apple.pkpass".into()),
+        (
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"pass.pkpass\"".into(),
+        ),
+        (
+            "last-modified".try_into().unwrap(),
+            Utc.from_utc_datetime(&last_updated_at)
+                .timestamp_millis()
+                .to_string(),
+        ),
+    ];
+
+    Ok((headers, body))
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The start of the last Sunday of March or October at 01:00 UTC, when the clocks in the EU are
+/// changed.
+fn daylight_saving_switch(year: i32, month: u32) -> DateTime<Utc> {
+    let last_day = NaiveDate::from_ymd_opt(year, month, 31).unwrap();
+    let last_sunday = last_day - Days::new(last_day.weekday().num_days_from_sunday().into());
+
+    Utc.from_utc_datetime(&last_sunday.and_hms_opt(1, 0, 0).unwrap())
+}
+
+/// The time in Germany, where the stores are: CET, or CEST from the last Sunday of March to the
+/// last Sunday of October.
+fn german_time(at: &DateTime<Utc>) -> DateTime<FixedOffset> {
+    let summer_time =
+        daylight_saving_switch(at.year(), 3) <= *at && *at < daylight_saving_switch(at.year(), 10);
+    let offset = FixedOffset::east_opt(if summer_time { 2 } else { 1 } * 60 * 60).unwrap();
+
+    at.with_timezone(&offset)
+}
+
+fn format_date(at: &DateTime<Utc>) -> String {
+    german_time(at).format("%d.%m.%Y %H:%M").to_string()
+}
+
+/// The rows of the pass details, as label and value.
+fn detail_rows(pass: &CustomerPassDetails) -> Vec<(&'static str, String)> {
+    match pass {
+        CustomerPassDetails::Loyality {
+            pass_holder_name,
+            current_points,
+            total_points,
+            already_redeemed,
+        } => vec![
+            ("Dieser Pass gehört", pass_holder_name.clone()),
+            (
+                "Gesammelte Stempel",
+                format!("{current_points} von {total_points} Stempeln"),
+            ),
+            ("Bereits eingelöst", already_redeemed.to_string()),
+        ],
+        CustomerPassDetails::Coupon {
+            discount_text,
+            details,
+            valid_from,
+            valid_until,
+            remaining_redemptions,
+        } => {
+            let mut rows = vec![("Gutschein", discount_text.clone())];
+            if let Some(details) = details {
+                rows.push(("Details", details.clone()));
+            }
+            if let Some(valid_from) = valid_from {
+                rows.push(("Gültig ab", format_date(valid_from)));
+            }
+            if let Some(valid_until) = valid_until {
+                rows.push(("Gültig bis", format_date(valid_until)));
+            }
+            rows.push(("Noch einlösbar", remaining_redemptions.to_string()));
+            rows
+        }
+        CustomerPassDetails::GiftCard {
+            pass_holder_name,
+            balance,
+            currency,
+        } => {
+            let mut rows = Vec::new();
+            if let Some(pass_holder_name) = pass_holder_name {
+                rows.push(("Dieser Pass gehört", pass_holder_name.clone()));
+            }
+            rows.push(("Guthaben", format_money(*balance, currency)));
+            rows
+        }
+        CustomerPassDetails::EventTicket {
+            attendee_name,
+            seat,
+            checked_in_at,
+        } => {
+            let mut rows = Vec::new();
+            if let Some(attendee_name) = attendee_name {
+                rows.push(("Gast", attendee_name.clone()));
+            }
+            if let Some(seat) = seat {
+                rows.push(("Platz", seat.clone()));
+            }
+            rows.push((
+                "Eingelassen",
+                checked_in_at
+                    .as_ref()
+                    .map(format_date)
+                    .unwrap_or_else(|| "Nein".into()),
+            ));
+            rows
+        }
+    }
+}
+
+fn history_row(entry: &CustomerHistoryEntryResponse, currency: Option<&str>) -> (String, String) {
+    match entry {
+        CustomerHistoryEntryResponse::Visit { at, store } => (
+            format_date(at),
+            match store {
+                Some(store) => format!("Besuch in {store}"),
+                None => "Besuch".into(),
+            },
+        ),
+        CustomerHistoryEntryResponse::GiftCardTransaction {
+            at,
+            amount,
+            balance_after,
+        } => {
+            let currency = currency.unwrap_or_default();
+            (
+                format_date(at),
+                format!(
+                    "{} (Guthaben danach {})",
+                    format_money(*amount, currency),
+                    format_money(*balance_after, currency)
+                ),
+            )
+        }
+    }
+}
+
+/// A minimal page without scripts or external resources, the links are shared with the customer.
+fn render_customer_view(view: &CustomerViewResponse, token: &str) -> String {
+    let currency = match &view.pass {
+        CustomerPassDetails::GiftCard { currency, .. } => Some(currency.as_str()),
+        _ => None,
+    };
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"de\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta name=\"robots\" content=\"noindex\">\n<title>Boulder Bubbletea Pass</title>\n\
+         <style>body{font-family:sans-serif;max-width:32rem;margin:2rem auto;padding:0 1rem}\
+         dt{color:#666;margin-top:.5rem}dd{margin:0;font-size:1.2rem}\
+         li{margin:.25rem 0}a.button{display:inline-block;padding:.75rem 1rem;background:#000;\
+         color:#fff;border-radius:.5rem;text-decoration:none}</style>\n</head>\n<body>\n\
+         <h1>Boulder Bubbletea</h1>\n<dl>\n",
+    );
+
+    for (label, value) in detail_rows(&view.pass) {
+        let _ = writeln!(
+            html,
+            "<dt>{}</dt><dd>{}</dd>",
+            escape_html(label),
+            escape_html(&value)
+        );
+    }
+    let _ = writeln!(
+        html,
+        "<dt>Mitgliedsnummer</dt><dd>{}</dd>\n</dl>",
+        escape_html(&view.member_code)
+    );
+
+    // Relative to this page, which is served at `/customer/passes/{serial_number}/{token}`.
+    let _ = writeln!(
+        html,
+        "<p><a class=\"button\" href=\"{}/pass.pkpass\">Zu Apple Wallet hinzufügen</a></p>",
+        escape_html(token)
+    );
+
+    html.push_str("<h2>Verlauf</h2>\n");
+    if view.history.is_empty() {
+        html.push_str("<p>Noch keine Einträge.</p>\n");
+    } else {
+        html.push_str("<ul>\n");
+        for entry in &view.history {
+            let (at, text) = history_row(entry, currency);
+            let _ = writeln!(
+                html,
+                "<li><time>{}</time> – {}</li>",
+                escape_html(&at),
+                escape_html(&text)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<p><small>Stand: {}</small></p>\n</body>\n</html>",
+        format_date(&view.last_updated_at)
+    );
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::format_date;
+
+    #[test]
+    fn formats_dates_in_german_time() {
+        // Winter time, UTC+1.
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 1, 15, 12, 30, 0).unwrap()),
+            "15.01.2026 13:30"
+        );
+        // Summer time, UTC+2, also across midnight.
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 7, 31, 22, 15, 0).unwrap()),
+            "01.08.2026 00:15"
+        );
+    }
+
+    #[test]
+    fn switches_at_the_last_sundays_of_march_and_october() {
+        // 2026-03-29 and 2026-10-25 are the last Sundays.
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 3, 29, 0, 59, 0).unwrap()),
+            "29.03.2026 01:59"
+        );
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap()),
+            "29.03.2026 03:00"
+        );
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 10, 25, 0, 59, 0).unwrap()),
+            "25.10.2026 02:59"
+        );
+        assert_eq!(
+            format_date(&Utc.with_ymd_and_hms(2026, 10, 25, 1, 0, 0).unwrap()),
+            "25.10.2026 02:00"
+        );
+    }
+}
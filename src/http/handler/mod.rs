@@ -1,7 +1,15 @@
 mod admin;
+mod certificate_health;
 mod create_pass;
+mod customer_view;
 mod health;
+mod recover_pass;
 
 pub use admin::*;
+pub use certificate_health::handle_certificate_health;
 pub use create_pass::handle_create_pass;
+pub use customer_view::{
+    handle_download_customer_pass, handle_get_customer_view, handle_get_customer_view_data,
+};
 pub use health::handle_health;
+pub use recover_pass::handle_recover_pass;
@@ -0,0 +1,38 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderName},
+};
+use chrono::{TimeZone, Utc};
+
+use crate::{http::AppState, wallet::body_from_package, Result};
+
+#[derive(serde::Deserialize)]
+pub struct RecoverPassPathParams {
+    pub recovery_token: String,
+}
+
+pub async fn handle_recover_pass(
+    State(state): State<AppState>,
+    Path(RecoverPassPathParams { recovery_token }): Path<RecoverPassPathParams>,
+) -> Result<([(HeaderName, String); 3], Body)> {
+    let (mut wallet_pass, last_updated_at) = state.app.recover_pass(&recovery_token).await?;
+
+    let body = body_from_package(&mut wallet_pass)?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/vnd.apple.pkpass".into()),
+        (
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"pass.pkpass\"".into(),
+        ),
+        (
+            "last-modified".try_into().unwrap(),
+            Utc.from_utc_datetime(&last_updated_at)
+                .timestamp_millis()
+                .to_string(),
+        ),
+    ];
+
+    Ok((headers, body))
+}
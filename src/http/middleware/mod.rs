@@ -1,5 +1,7 @@
 mod oidc_auth;
+mod rate_limit;
 mod request_tracing;
 
 pub use oidc_auth::*;
+pub use rate_limit::*;
 pub use request_tracing::setup_request_tracing;
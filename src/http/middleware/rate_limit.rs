@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex,
+    },
+};
+
+use axum::{
+    extract::{rejection::RawPathParamsRejection, ConnectInfo, RawPathParams, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::{
+    db::queries::{delete_stale_rate_limit_buckets, hit_rate_limit_bucket},
+    http::AppState,
+    Error, Result,
+};
+
+/// Remove stale buckets once the in memory store holds more than this many.
+const MEMORY_STORE_PRUNE_THRESHOLD: usize = 10_000;
+
+/// How often stale buckets are removed from the database, in seconds.
+const POSTGRES_STORE_CLEANUP_INTERVAL: i64 = 60 * 60;
+
+/// An allowed amount of requests per time window, written as e.g. `20/1h`, `60/1m` or `5/30s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitRate {
+    pub limit: u32,
+    pub window_secs: u32,
+}
+
+impl FromStr for RateLimitRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid rate limit {s:?}, expected e.g. 20/1h, 60/1m or 5/30s");
+
+        let (limit, window) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let limit = limit.parse::<u32>().map_err(|_| invalid())?;
+
+        let unit_index = window
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (amount, unit) = window.split_at(unit_index);
+        let amount = amount.parse::<u32>().map_err(|_| invalid())?;
+
+        let window_secs = match unit {
+            "s" => Some(amount),
+            "m" => amount.checked_mul(60),
+            "h" => amount.checked_mul(60 * 60),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+
+        if limit == 0 || window_secs == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self { limit, window_secs })
+    }
+}
+
+impl<'de> Deserialize<'de> for RateLimitRate {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserializes an optional rate limit, where `off` disables the limit.
+pub fn deserialize_optional_rate_limit<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<RateLimitRate>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.trim() {
+        "off" => Ok(None),
+        rate => rate.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+impl fmt::Display for RateLimitRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}s", self.limit, self.window_secs)
+    }
+}
+
+/// The groups of routes that are limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    CreatePass,
+    AppleLog,
+    AppleRegistration,
+    CustomerPassDownload,
+}
+
+impl RateLimitScope {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::CreatePass => "create_pass",
+            Self::AppleLog => "apple_log",
+            Self::AppleRegistration => "apple_registration",
+            Self::CustomerPassDownload => "customer_pass_download",
+        }
+    }
+}
+
+/// What requests are grouped by when counting them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKey {
+    Ip,
+    DeviceLibraryId,
+    SerialNumber,
+}
+
+impl RateLimitKey {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Ip => "ip",
+            Self::DeviceLibraryId => "device_library_id",
+            Self::SerialNumber => "serial_number",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitRules {
+    pub create_pass_per_ip: Option<RateLimitRate>,
+    pub apple_log_per_ip: Option<RateLimitRate>,
+    pub apple_registration_per_ip: Option<RateLimitRate>,
+    pub apple_registration_per_device: Option<RateLimitRate>,
+    pub apple_registration_per_serial_number: Option<RateLimitRate>,
+    pub customer_pass_download_per_ip: Option<RateLimitRate>,
+    pub customer_pass_download_per_serial_number: Option<RateLimitRate>,
+}
+
+impl RateLimitRules {
+    fn for_scope(&self, scope: RateLimitScope) -> Vec<(RateLimitKey, RateLimitRate)> {
+        let rules = match scope {
+            RateLimitScope::CreatePass => vec![(RateLimitKey::Ip, self.create_pass_per_ip)],
+            RateLimitScope::AppleLog => vec![(RateLimitKey::Ip, self.apple_log_per_ip)],
+            RateLimitScope::AppleRegistration => vec![
+                (RateLimitKey::Ip, self.apple_registration_per_ip),
+                (
+                    RateLimitKey::DeviceLibraryId,
+                    self.apple_registration_per_device,
+                ),
+                (
+                    RateLimitKey::SerialNumber,
+                    self.apple_registration_per_serial_number,
+                ),
+            ],
+            RateLimitScope::CustomerPassDownload => vec![
+                (RateLimitKey::Ip, self.customer_pass_download_per_ip),
+                (
+                    RateLimitKey::SerialNumber,
+                    self.customer_pass_download_per_serial_number,
+                ),
+            ],
+        };
+
+        rules
+            .into_iter()
+            .filter_map(|(key, rate)| rate.map(|rate| (key, rate)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryRateLimitStore {
+    /// Start of the current window and the hits in it, per bucket.
+    buckets: Mutex<HashMap<String, (i64, u32)>>,
+}
+
+impl MemoryRateLimitStore {
+    fn hit(&self, bucket: &str, window_start: i64, window_secs: i64) -> u32 {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MEMORY_STORE_PRUNE_THRESHOLD {
+            // Every window is at most a day long in practice, older buckets can't count anymore.
+            let min_window_start = window_start - window_secs.max(24 * 60 * 60);
+            buckets.retain(|_, (start, _)| *start >= min_window_start);
+        }
+
+        let entry = buckets
+            .entry(bucket.to_string())
+            .or_insert((window_start, 0));
+        if entry.0 != window_start {
+            *entry = (window_start, 0);
+        }
+        entry.1 += 1;
+
+        entry.1
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresRateLimitStore {
+    db_pool: PgPool,
+    last_cleanup: AtomicI64,
+}
+
+impl PostgresRateLimitStore {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            last_cleanup: AtomicI64::new(0),
+        }
+    }
+
+    async fn hit(&self, bucket: &str, window_start: i64, now: i64) -> Result<u32> {
+        let hits = hit_rate_limit_bucket(bucket, timestamp(window_start), &self.db_pool).await?;
+
+        let last_cleanup = self.last_cleanup.load(Ordering::Relaxed);
+        if now - last_cleanup > POSTGRES_STORE_CLEANUP_INTERVAL
+            && self
+                .last_cleanup
+                .compare_exchange(last_cleanup, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            let deleted =
+                delete_stale_rate_limit_buckets(timestamp(now - 24 * 60 * 60), &self.db_pool)
+                    .await?;
+            info!(count = deleted, "deleted stale rate limit buckets");
+        }
+
+        Ok(hits.try_into().unwrap_or(u32::MAX))
+    }
+}
+
+/// Where the hits are counted. The in memory store is per process, the Postgres store is shared
+/// between all instances of the server.
+#[derive(Debug)]
+pub enum RateLimitStore {
+    Memory(MemoryRateLimitStore),
+    Postgres(PostgresRateLimitStore),
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    store: RateLimitStore,
+    rules: RateLimitRules,
+    /// Use the last entry of `X-Forwarded-For` as client ip, for running behind a reverse proxy.
+    trust_forwarded_for: bool,
+}
+
+impl RateLimiter {
+    pub fn new(store: RateLimitStore, rules: RateLimitRules, trust_forwarded_for: bool) -> Self {
+        Self {
+            store,
+            rules,
+            trust_forwarded_for,
+        }
+    }
+
+    /// Counts a request and returns the seconds until it may be retried, if it exceeds the limit.
+    async fn check(
+        &self,
+        scope: RateLimitScope,
+        key: RateLimitKey,
+        value: &str,
+        rate: RateLimitRate,
+    ) -> Result<Option<u64>> {
+        let now = Utc::now().timestamp();
+        let window_secs = i64::from(rate.window_secs);
+        let window_start = now - now.rem_euclid(window_secs);
+        let bucket = format!("{}:{}:{}:{}", scope.name(), key.name(), rate, value);
+
+        let hits = match &self.store {
+            RateLimitStore::Memory(store) => store.hit(&bucket, window_start, window_secs),
+            RateLimitStore::Postgres(store) => store.hit(&bucket, window_start, now).await?,
+        };
+
+        if hits <= rate.limit {
+            return Ok(None);
+        }
+
+        Ok(Some((window_start + window_secs - now).max(1) as u64))
+    }
+
+    fn client_ip(&self, headers: &HeaderMap, connect_info: Option<SocketAddr>) -> Option<IpAddr> {
+        if self.trust_forwarded_for {
+            let forwarded_for = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .last()
+                .and_then(|ip| ip.trim().parse().ok());
+
+            if forwarded_for.is_some() {
+                return forwarded_for;
+            }
+        }
+
+        connect_info.map(|addr| addr.ip())
+    }
+}
+
+fn timestamp(secs: i64) -> chrono::NaiveDateTime {
+    Utc.timestamp_opt(secs, 0)
+        .single()
+        .map(|d: DateTime<Utc>| d.naive_utc())
+        .unwrap_or_default()
+}
+
+/// Rejects requests of `scope` that exceed any of its configured limits. Must be added with
+/// `route_layer`, so the path parameters are known.
+pub async fn rate_limit(
+    State((state, scope)): State<(AppState, RateLimitScope)>,
+    path_params: std::result::Result<RawPathParams, RawPathParamsRejection>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    let rate_limiter = &state.rate_limiter;
+    let path_param = |name: &str| {
+        path_params.as_ref().ok().and_then(|params| {
+            params
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.to_string())
+        })
+    };
+
+    for (key, rate) in rate_limiter.rules.for_scope(scope) {
+        let value = match key {
+            RateLimitKey::Ip => rate_limiter
+                .client_ip(
+                    req.headers(),
+                    req.extensions()
+                        .get::<ConnectInfo<SocketAddr>>()
+                        .map(|ConnectInfo(addr)| *addr),
+                )
+                .map(|ip| ip.to_string()),
+            RateLimitKey::DeviceLibraryId => path_param("device_library_id"),
+            RateLimitKey::SerialNumber => path_param("serial_number"),
+        };
+
+        let Some(value) = value else {
+            continue;
+        };
+
+        if let Some(retry_after) = rate_limiter.check(scope, key, &value, rate).await? {
+            warn!(
+                scope = scope.name(),
+                key = key.name(),
+                value = value,
+                "rate limit exceeded"
+            );
+            return Err(Error::RateLimited { retry_after });
+        }
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimitRate;
+
+    #[test]
+    fn parses_rates() {
+        assert_eq!(
+            "20/1h".parse(),
+            Ok(RateLimitRate {
+                limit: 20,
+                window_secs: 60 * 60
+            })
+        );
+        assert_eq!(
+            "5/30s".parse(),
+            Ok(RateLimitRate {
+                limit: 5,
+                window_secs: 30
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_windows_that_overflow() {
+        assert!("1/4294967295m".parse::<RateLimitRate>().is_err());
+        assert!("1/1193047h".parse::<RateLimitRate>().is_err());
+    }
+}
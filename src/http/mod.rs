@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 mod client_error;
 
+mod extractors;
 mod handler;
 mod middleware;
 mod router;
@@ -9,11 +10,15 @@ mod router;
 pub use router::start;
 use sqlx::PgPool;
 
-use crate::{app::App, apple::ApnClient};
+use crate::app::App;
 
 pub use client_error::ClientError;
 
-pub use self::middleware::{OidcSub, OidcValidator};
+pub use self::middleware::{
+    deserialize_optional_rate_limit, MemoryRateLimitStore, OidcSub, OidcValidator,
+    PostgresRateLimitStore, RateLimitRate, RateLimitRules, RateLimitStore, RateLimiter,
+};
+pub(crate) use self::middleware::{rate_limit, RateLimitScope};
 
 pub type AppState = Arc<InnerAppState>;
 
@@ -21,6 +26,17 @@ pub type AppState = Arc<InnerAppState>;
 pub struct InnerAppState {
     pub app: App,
     pub db_pool: PgPool,
-    pub apn_client: ApnClient,
     pub oidc_validator: OidcValidator,
+    pub rate_limiter: RateLimiter,
+    /// Public base url of this server, used to build links for customers.
+    pub public_url: Option<String>,
+}
+
+impl InnerAppState {
+    /// Absolute url of `path` on this server, if the public url is configured.
+    pub fn public_link(&self, path: &str) -> Option<String> {
+        self.public_url
+            .as_ref()
+            .map(|url| format!("{}{path}", url.trim_end_matches('/')))
+    }
 }
@@ -1,6 +1,8 @@
+use std::net::SocketAddr;
+
 use axum::{
     http::header::{AUTHORIZATION, CONTENT_TYPE},
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
 use tokio::net::TcpListener;
@@ -12,7 +14,7 @@ use crate::{
     apple,
     http::{
         handler,
-        middleware::{oidc_auth, setup_request_tracing},
+        middleware::{oidc_auth, rate_limit, setup_request_tracing, RateLimitScope},
     },
     Error, Result,
 };
@@ -33,14 +35,101 @@ pub async fn start(host: &str, state: AppState) -> Result<()> {
             "/passes/{serial_number}/loyality",
             get(handler::handle_get_loyality_pass),
         )
+        .route(
+            "/passes/{serial_number}/coupon/redemption",
+            post(handler::handle_redeem_coupon),
+        )
+        .route(
+            "/passes/{serial_number}/coupon",
+            get(handler::handle_get_coupon),
+        )
+        .route(
+            "/passes/{serial_number}/gift-card/top-ups",
+            post(handler::handle_top_up_gift_card),
+        )
+        .route(
+            "/passes/{serial_number}/gift-card/spendings",
+            post(handler::handle_spend_gift_card),
+        )
+        .route(
+            "/passes/{serial_number}/gift-card/transactions",
+            get(handler::handle_list_gift_card_transactions),
+        )
+        .route(
+            "/passes/{serial_number}/gift-card",
+            get(handler::handle_get_gift_card),
+        )
+        .route(
+            "/passes/{serial_number}/event-ticket/check-in",
+            post(handler::handle_check_in_event_ticket),
+        )
+        .route(
+            "/passes/{serial_number}/event-ticket",
+            get(handler::handle_get_event_ticket),
+        )
+        .route(
+            "/passes/{serial_number}/auth-tokens/rotation",
+            post(handler::handle_rotate_auth_tokens),
+        )
+        .route(
+            "/passes/{serial_number}/recovery",
+            post(handler::handle_create_pass_recovery),
+        )
+        .route(
+            "/passes/{serial_number}",
+            patch(handler::handle_update_pass_holder),
+        )
+        .route("/passes", get(handler::handle_list_loyality_passes))
+        .route("/device-logs", get(handler::handle_list_device_logs))
+        .route("/coupons", post(handler::handle_create_coupon))
+        .route("/gift-cards", post(handler::handle_create_gift_card))
+        .route(
+            "/loyality-passes",
+            post(handler::handle_create_loyality_pass),
+        )
+        .route("/scans", post(handler::handle_scan_pass))
+        .route("/events", post(handler::handle_create_event))
+        .route("/events/{event_id}", get(handler::handle_get_event))
+        .route(
+            "/events/{event_id}/tickets",
+            post(handler::handle_issue_event_tickets),
+        )
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             oidc_auth,
         ))
         .route("/health", get(handler::handle_health))
+        .route(
+            "/health/certificates",
+            get(handler::handle_certificate_health),
+        )
         .route(
             "/passes",
-            get(handler::handle_create_pass).post(handler::handle_create_pass),
+            post(handler::handle_create_pass).route_layer(axum::middleware::from_fn_with_state(
+                (state.clone(), RateLimitScope::CreatePass),
+                rate_limit,
+            )),
+        )
+        .route(
+            "/recovery/{recovery_token}",
+            get(handler::handle_recover_pass),
+        )
+        .route(
+            "/customer/passes/{serial_number}/{token}",
+            get(handler::handle_get_customer_view),
+        )
+        .route(
+            "/customer/passes/{serial_number}/{token}/data",
+            get(handler::handle_get_customer_view_data),
+        )
+        .route(
+            "/customer/passes/{serial_number}/{token}/pass.pkpass",
+            get(handler::handle_download_customer_pass).route_layer(
+                axum::middleware::from_fn_with_state(
+                    (state.clone(), RateLimitScope::CustomerPassDownload),
+                    rate_limit,
+                ),
+            ),
         )
         .with_state(state.clone())
         .nest("/apple-webhooks", apple::router(state.clone()))
@@ -60,7 +149,10 @@ pub async fn start(host: &str, state: AppState) -> Result<()> {
 
     info!("Starting listening on {}", host);
 
-    axum::serve(listener, app.into_make_service())
-        .await
-        .map_err(Error::IO)
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(Error::IO)
 }
@@ -1,9 +1,12 @@
 pub mod app;
 pub mod apple;
 pub mod db;
+pub mod email;
 mod error;
 pub mod http;
 pub mod image;
+mod member_code;
+mod token;
 mod trace;
 pub mod wallet;
 
@@ -0,0 +1,144 @@
+use crate::Result;
+
+/// Crockford's base32 alphabet, followed by the five extra symbols used for the check symbol.
+const ALPHABET: &[u8; 37] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+/// Number of base32 symbols before the check symbol, 35 bits of randomness.
+const DATA_LENGTH: usize = 7;
+
+/// Generates a random member code of seven Crockford base32 symbols and a check symbol, e.g.
+/// `4K7M2QXA`. The codes are short enough to be dictated over the phone.
+pub fn generate() -> Result<String> {
+    let mut buf = [0; 8];
+    openssl::rand::rand_bytes(&mut buf)?;
+
+    let value = u64::from_be_bytes(buf) >> (64 - 5 * DATA_LENGTH);
+
+    Ok(encode(value))
+}
+
+fn encode(value: u64) -> String {
+    let mut code: Vec<u8> = (0..DATA_LENGTH)
+        .rev()
+        .map(|i| ALPHABET[((value >> (5 * i)) & 0x1f) as usize])
+        .collect();
+    code.push(ALPHABET[(value % 37) as usize]);
+
+    String::from_utf8(code).expect("the alphabet is ascii")
+}
+
+/// Turns user input into a member code, accepting lowercase letters, the ambiguous letters
+/// `I`, `L` and `O` and separating hyphens and spaces. Returns `None` if the check symbol does not
+/// match.
+pub fn normalize(input: &str) -> Option<String> {
+    let symbols: Vec<u8> = input
+        .bytes()
+        .filter(|b| *b != b'-' && *b != b' ')
+        .map(|b| match b.to_ascii_uppercase() {
+            b'I' | b'L' => b'1',
+            b'O' => b'0',
+            b => b,
+        })
+        .collect();
+
+    let (check, data) = symbols.split_last()?;
+    if data.len() != DATA_LENGTH {
+        return None;
+    }
+
+    let value = data.iter().try_fold(0u64, |value, symbol| {
+        let digit = ALPHABET[..32].iter().position(|a| a == symbol)?;
+        Some((value << 5) | digit as u64)
+    })?;
+
+    let code = encode(value);
+    (code.as_bytes()[DATA_LENGTH] == *check).then_some(code)
+}
+
+/// Formats a member code for reading, e.g. `4K7M-2QXA`.
+pub fn display(code: &str) -> String {
+    match code.split_at_checked(4) {
+        Some((first, second)) => format!("{first}-{second}"),
+        None => code.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display, encode, generate, normalize, ALPHABET, DATA_LENGTH};
+
+    /// The code with the data symbols `10ABCDE`.
+    fn code_with_ambiguous_symbols() -> String {
+        let value = [1, 0, 10, 11, 12, 13, 14]
+            .into_iter()
+            .fold(0u64, |value, digit| (value << 5) | digit);
+
+        encode(value)
+    }
+
+    #[test]
+    fn generated_codes_round_trip() {
+        for _ in 0..100 {
+            let code = generate().unwrap();
+
+            assert_eq!(code.len(), DATA_LENGTH + 1);
+            assert_eq!(normalize(&code), Some(code.clone()));
+            assert_eq!(normalize(&display(&code)), Some(code.clone()));
+        }
+    }
+
+    #[test]
+    fn rejects_a_wrong_check_symbol() {
+        let code = code_with_ambiguous_symbols();
+        let (data, check) = code.split_at(DATA_LENGTH);
+
+        for symbol in ALPHABET.iter().map(|s| *s as char) {
+            if symbol.to_string() != check {
+                assert_eq!(
+                    normalize(&format!("{data}{symbol}")),
+                    None,
+                    "{data}{symbol}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_one_changed_symbol() {
+        let code = code_with_ambiguous_symbols();
+
+        for position in 0..DATA_LENGTH {
+            for symbol in &ALPHABET[..32] {
+                let mut changed = code.clone().into_bytes();
+                if changed[position] == *symbol {
+                    continue;
+                }
+                changed[position] = *symbol;
+
+                let changed = String::from_utf8(changed).unwrap();
+                assert_eq!(normalize(&changed), None, "{changed}");
+            }
+        }
+    }
+
+    #[test]
+    fn accepts_lowercase_separators_and_ambiguous_letters() {
+        let code = code_with_ambiguous_symbols();
+        assert!(code.starts_with("10ABCDE"));
+
+        let check = &code[DATA_LENGTH..];
+        for input in [
+            code.to_lowercase(),
+            display(&code),
+            format!("10AB CDE{check}"),
+            format!(" 10-ab cd-e{} ", check.to_lowercase()),
+            format!("IOABCDE{check}"),
+            format!("loABCDE{check}"),
+        ] {
+            assert_eq!(normalize(&input), Some(code.clone()), "{input:?}");
+        }
+
+        assert_eq!(normalize(&code[..DATA_LENGTH]), None);
+        assert_eq!(normalize(&format!("{code}0")), None);
+    }
+}
@@ -0,0 +1,46 @@
+use openssl::{hash::MessageDigest, memcmp, pkey::PKey, sha::sha256, sign::Signer};
+
+use crate::Result;
+
+/// Length of generated tokens in bytes.
+const TOKEN_LENGTH: usize = 32;
+
+/// Generates a random, hex encoded token.
+pub fn generate() -> Result<String> {
+    let mut buf = [0; TOKEN_LENGTH];
+    openssl::rand::rand_bytes(&mut buf)?;
+
+    Ok(hex::encode(buf))
+}
+
+/// Derives a token from `token` with `key`, the same inputs always give the same token.
+pub fn derive(key: &str, token: &str) -> Result<String> {
+    hmac(key.as_bytes(), token, TOKEN_LENGTH)
+}
+
+/// Hashes a token for storing it in the database. The tokens are random, so a fast hash is
+/// enough.
+pub fn hash(token: &str) -> String {
+    hex::encode(sha256(token.as_bytes()))
+}
+
+/// Hex encoded HMAC-SHA256 of `message`, truncated to `length` bytes.
+pub fn hmac(secret: &[u8], message: &str, length: usize) -> Result<String> {
+    let key = PKey::hmac(secret)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(message.as_bytes())?;
+
+    Ok(hex::encode(&signer.sign_to_vec()?[..length]))
+}
+
+/// Checks in constant time whether the hex encoded `signature` equals `expected`.
+pub fn signature_eq(signature: &str, expected: &str) -> bool {
+    signature.len() == expected.len() && memcmp::eq(signature.as_bytes(), expected.as_bytes())
+}
+
+/// Checks in constant time whether `token` belongs to `token_hash`.
+pub fn verify(token: &str, token_hash: &str) -> bool {
+    let hashed = hash(token);
+
+    signature_eq(&hashed, token_hash)
+}
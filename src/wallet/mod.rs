@@ -14,11 +14,43 @@ use passes::{
     sign::{self, SignConfig},
     visual_appearance::{Color, VisualAppearance},
     web_service::WebService,
-    Package, PassBuilder, PassConfig,
+    Package, Pass, PassBuilder, PassConfig,
 };
 use tokio_util::io::ReaderStream;
 
-use crate::{image::ImageMaker, Error, Result};
+use crate::{image::ImageMaker, member_code, Result};
+
+use self::package::write_package;
+
+mod package;
+
+pub struct CouponPass {
+    pub discount_text: String,
+    pub details: Option<String>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub max_redemptions: i32,
+    pub redemptions: i32,
+}
+
+pub struct EventTicketPass {
+    pub event_id: String,
+    pub event_name: String,
+    pub venue: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub attendee_name: Option<String>,
+    pub seat: Option<String>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+}
+
+pub struct GiftCardPass {
+    /// In minor currency units, e.g. cents.
+    pub balance: i64,
+    pub currency: String,
+    pub pass_holder_name: Option<String>,
+    pub last_use: Option<DateTime<Utc>>,
+}
 
 pub struct LoyalityPass {
     pub already_redeemed: i32,
@@ -26,13 +58,84 @@ pub struct LoyalityPass {
     pub current_points: i32,
     pub pass_holder_name: String,
     pub last_use: Option<DateTime<Utc>>,
+    pub tier: Option<LoyalityTier>,
+    /// Whether membership tiers are configured, passes below the lowest tier are then shown in
+    /// the base tier.
+    pub tiers_configured: bool,
+}
+
+/// Shown for passes that didn't reach a membership tier yet. The tier field has to be on the pass
+/// from the start, Wallet shows no change message for fields that are added.
+const BASE_TIER_LABEL: &str = "Basis";
+
+/// The membership tier a loyality pass reached, with its colors as RGB.
+pub struct LoyalityTier {
+    pub label: String,
+    pub background_color: [u8; 3],
+    pub foreground_color: [u8; 3],
+    pub label_color: [u8; 3],
+}
+
+/// Barcode formats Wallet can show, configured per pass type for the scanners of the store.
+#[derive(serde::Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BarcodeKind {
+    #[default]
+    Qr,
+    Pdf417,
+    Aztec,
+    Code128,
+}
+
+/// How a pass is identified by Wallet, the web service, the scanners and the customer.
+pub struct PassIdentity {
+    pub serial_number: String,
+    pub authentication_token: String,
+    pub member_code: String,
+    pub barcode: PassBarcode,
+    /// Link to the customer view of the pass, shown on the back.
+    pub customer_view_url: Option<String>,
+}
+
+/// What the barcode of a pass encodes.
+pub struct PassBarcode {
+    pub kind: BarcodeKind,
+    pub message: String,
+    /// Shown below the barcode, for typing it in if it can't be scanned.
+    pub alt_text: Option<String>,
 }
 
+impl From<PassBarcode> for Barcode {
+    fn from(barcode: PassBarcode) -> Self {
+        Self {
+            message: barcode.message,
+            format: match barcode.kind {
+                BarcodeKind::Qr => BarcodeFormat::QR,
+                BarcodeKind::Pdf417 => BarcodeFormat::PDF417,
+                BarcodeKind::Aztec => BarcodeFormat::Aztec,
+                BarcodeKind::Code128 => BarcodeFormat::Code128,
+            },
+            alt_text: barcode.alt_text,
+            ..Default::default()
+        }
+    }
+}
+
+/// The kinds of passes, each kind can be served by one or more pass type identifiers.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum PassKind {
+    Loyality,
+    Coupon,
+    EventTicket,
+    GiftCard,
+}
+
+/// Builds the passes. The texts are German, the change messages and the fields they belong to are
+/// translated with the `.lproj` string files written with the package.
 #[derive(Debug)]
 pub struct PassMaker {
     team_identifier: String,
-    pass_type_identifier: String,
-    i_sign_config: ISignConfig,
     web_service_url: String,
     _logo_path: String,
     icon_path: String,
@@ -41,18 +144,14 @@ pub struct PassMaker {
 
 impl PassMaker {
     pub fn new(
-        i_sign_config: ISignConfig,
         team_identifier: String,
-        pass_type_identifier: String,
         web_service_url: String,
         logo_path: String,
         icon_path: String,
         image_maker: ImageMaker,
     ) -> Result<Self> {
         Ok(Self {
-            i_sign_config,
             team_identifier,
-            pass_type_identifier,
             web_service_url,
             _logo_path: logo_path,
             icon_path,
@@ -60,27 +159,39 @@ impl PassMaker {
         })
     }
 
-    pub fn pass_type_identifier(&self) -> &str {
-        &self.pass_type_identifier
-    }
-
     pub fn new_loyality_pass(
         &self,
-        serial_number: String,
-        authentication_token: String,
+        pass_type_identifier: &str,
+        i_sign_config: &ISignConfig,
+        identity: PassIdentity,
         loyality_pass: LoyalityPass,
     ) -> Result<Package> {
+        let PassIdentity {
+            serial_number,
+            authentication_token,
+            member_code,
+            barcode,
+            customer_view_url,
+        } = identity;
+
         let pass = PassBuilder::new(PassConfig {
             organization_name: "Boulder Bubbletea".into(),
             description: "Boulder Bubbletea Pass".into(),
-            pass_type_identifier: self.pass_type_identifier.clone(),
+            pass_type_identifier: pass_type_identifier.to_string(),
             team_identifier: self.team_identifier.clone(),
             serial_number: serial_number.clone(),
         })
-        .appearance(VisualAppearance {
-            label_color: Color::white(),
-            foreground_color: Color::white(),
-            background_color: Color::new(255, 145, 160),
+        .appearance(match &loyality_pass.tier {
+            Some(tier) => VisualAppearance {
+                label_color: rgb(tier.label_color),
+                foreground_color: rgb(tier.foreground_color),
+                background_color: rgb(tier.background_color),
+            },
+            None => VisualAppearance {
+                label_color: Color::white(),
+                foreground_color: Color::white(),
+                background_color: Color::new(255, 145, 160),
+            },
         })
         .set_sharing_prohibited(true)
         .fields({
@@ -109,6 +220,9 @@ impl PassMaker {
                 fields::ContentOptions {
                     label: "Bereits eingelöst".to_string().into(),
                     text_alignment: Some(TextAlignment::Right),
+                    change_message: "Bonus eingelöst! Insgesamt schon %@ mal."
+                        .to_string()
+                        .into(),
                     ..Default::default()
                 },
             ))
@@ -119,16 +233,57 @@ impl PassMaker {
                     label: "Bonus".to_string().into(),
                     ..Default::default()
                 },
-            ))
-            .add_back_field(fields::Content::new(
-                "serial-number",
+            ));
+
+            f = identity_back_fields(
+                f,
+                &member_code,
                 &serial_number,
+                customer_view_url.as_deref(),
+            );
+
+            // The points are also shown as text, the strip image is not read by VoiceOver and
+            // not shown on the lock screen. Primary fields are drawn over the strip image and
+            // coupons allow only four secondary and auxiliary fields together, so the points and
+            // what is left until the bonus share one auxiliary field.
+            let remaining = (loyality_pass.total_points - loyality_pass.current_points).max(0);
+
+            f = f.add_auxiliary_field(fields::Content::new(
+                "points",
+                &format!(
+                    "{} von {} Stempeln",
+                    loyality_pass.current_points, loyality_pass.total_points
+                ),
                 fields::ContentOptions {
-                    label: String::from("Serial Number").into(),
+                    label: match remaining {
+                        0 => "Gesammelt, Bonus bereit".to_string(),
+                        1 => "Gesammelt, noch 1 bis zum Bonus".to_string(),
+                        n => format!("Gesammelt, noch {n} bis zum Bonus"),
+                    }
+                    .into(),
+                    // Points drop to zero when the bonus is redeemed, that is announced by the
+                    // redeemed field instead.
+                    change_message: (loyality_pass.current_points > 0)
+                        .then(|| "Neuer Stempel! Du hast jetzt %@.".to_string()),
                     ..Default::default()
                 },
             ));
 
+            if loyality_pass.tiers_configured {
+                f = f.add_header_field(fields::Content::new(
+                    "tier",
+                    loyality_pass
+                        .tier
+                        .as_ref()
+                        .map_or(BASE_TIER_LABEL, |tier| tier.label.as_str()),
+                    fields::ContentOptions {
+                        label: "Status".to_string().into(),
+                        change_message: "Neuer Status: %@".to_string().into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
             if let Some(last_use) = loyality_pass.last_use {
                 f = f.add_back_field(fields::Content::new(
                     "last_use",
@@ -143,17 +298,368 @@ impl PassMaker {
             }
             f
         })
-        .add_barcode(Barcode {
-            message: serial_number,
-            format: BarcodeFormat::QR,
-            ..Default::default()
+        .add_barcode(barcode.into())
+        .web_service(WebService {
+            web_service_url: self.web_service_url.clone(),
+            authentication_token,
         })
+        .build();
+
+        let mut package = self.new_package(pass, i_sign_config)?;
+
+        package
+            .add_resource(
+                resource::Type::Strip(resource::Version::Size2X),
+                Cursor::new(self.image_maker.generate_points_image(
+                    loyality_pass.total_points.try_into().unwrap(),
+                    loyality_pass.current_points.try_into().unwrap(),
+                )?),
+            )
+            .unwrap();
+
+        Ok(package)
+    }
+
+    pub fn new_coupon_pass(
+        &self,
+        pass_type_identifier: &str,
+        i_sign_config: &ISignConfig,
+        identity: PassIdentity,
+        coupon_pass: CouponPass,
+    ) -> Result<Package> {
+        let PassIdentity {
+            serial_number,
+            authentication_token,
+            member_code,
+            barcode,
+            customer_view_url,
+        } = identity;
+
+        let remaining = coupon_pass.max_redemptions - coupon_pass.redemptions;
+
+        let mut builder = PassBuilder::new(PassConfig {
+            organization_name: "Boulder Bubbletea".into(),
+            description: "Boulder Bubbletea Gutschein".into(),
+            pass_type_identifier: pass_type_identifier.to_string(),
+            team_identifier: self.team_identifier.clone(),
+            serial_number: serial_number.clone(),
+        })
+        .appearance(VisualAppearance {
+            label_color: Color::white(),
+            foreground_color: Color::white(),
+            background_color: Color::new(255, 145, 160),
+        })
+        .set_sharing_prohibited(true)
+        // Used up coupons are shown as such and can't be scanned anymore.
+        .voided(remaining <= 0)
+        .fields({
+            let mut f = fields::Type::Coupon {
+                pass_fields: fields::Fields::default(),
+            }
+            .add_header_field(fields::Content::new(
+                "name",
+                "Boulder Bubbletea",
+                fields::ContentOptions {
+                    label: "Store".to_string().into(),
+                    ..Default::default()
+                },
+            ))
+            .add_primary_field(fields::Content::new(
+                "discount",
+                &coupon_pass.discount_text,
+                fields::ContentOptions {
+                    label: "Gutschein".to_string().into(),
+                    ..Default::default()
+                },
+            ));
+
+            if let Some(valid_until) = coupon_pass.valid_until {
+                f = f.add_secondary_field(fields::Content::new(
+                    "valid_until",
+                    &valid_until.to_rfc3339(),
+                    fields::ContentOptions {
+                        label: "Gültig bis".to_string().into(),
+                        date_style: DateStyle::Medium.into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if coupon_pass.max_redemptions > 1 {
+                f = f.add_secondary_field(fields::Content::new(
+                    "remaining",
+                    &remaining.max(0).to_string(),
+                    fields::ContentOptions {
+                        label: "Noch einlösbar".to_string().into(),
+                        text_alignment: Some(TextAlignment::Right),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if let Some(details) = &coupon_pass.details {
+                f = f.add_back_field(fields::Content::new(
+                    "details",
+                    details,
+                    fields::ContentOptions {
+                        label: "Details".to_string().into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if let Some(valid_from) = coupon_pass.valid_from {
+                f = f.add_back_field(fields::Content::new(
+                    "valid_from",
+                    &valid_from.to_rfc3339(),
+                    fields::ContentOptions {
+                        label: "Gültig ab".to_string().into(),
+                        time_style: DateStyle::Short.into(),
+                        date_style: DateStyle::Medium.into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            identity_back_fields(
+                f,
+                &member_code,
+                &serial_number,
+                customer_view_url.as_deref(),
+            )
+        })
+        .add_barcode(barcode.into())
+        .web_service(WebService {
+            web_service_url: self.web_service_url.clone(),
+            authentication_token,
+        });
+
+        if let Some(valid_until) = coupon_pass.valid_until {
+            builder = builder.expiration_date(valid_until);
+        }
+
+        self.new_package(builder.build(), i_sign_config)
+    }
+
+    pub fn new_event_ticket_pass(
+        &self,
+        pass_type_identifier: &str,
+        i_sign_config: &ISignConfig,
+        identity: PassIdentity,
+        event_ticket_pass: EventTicketPass,
+    ) -> Result<Package> {
+        let PassIdentity {
+            serial_number,
+            authentication_token,
+            member_code,
+            barcode,
+            customer_view_url,
+        } = identity;
+
+        let mut builder = PassBuilder::new(PassConfig {
+            organization_name: "Boulder Bubbletea".into(),
+            description: format!("Ticket: {}", event_ticket_pass.event_name),
+            pass_type_identifier: pass_type_identifier.to_string(),
+            team_identifier: self.team_identifier.clone(),
+            serial_number: serial_number.clone(),
+        })
+        .appearance(VisualAppearance {
+            label_color: Color::white(),
+            foreground_color: Color::white(),
+            background_color: Color::new(255, 145, 160),
+        })
+        // Wallet shows the tickets of one event as a stack.
+        .grouping_identifier(event_ticket_pass.event_id.clone())
+        .relevant_date(event_ticket_pass.starts_at)
+        .set_sharing_prohibited(true)
+        // Used tickets can't be scanned again.
+        .voided(event_ticket_pass.checked_in_at.is_some())
+        .fields({
+            let mut f = fields::Type::EventTicket {
+                pass_fields: fields::Fields::default(),
+            }
+            .add_header_field(fields::Content::new(
+                "starts_at",
+                &event_ticket_pass.starts_at.to_rfc3339(),
+                fields::ContentOptions {
+                    label: "Beginn".to_string().into(),
+                    time_style: DateStyle::Short.into(),
+                    date_style: DateStyle::Short.into(),
+                    ..Default::default()
+                },
+            ))
+            .add_primary_field(fields::Content::new(
+                "event",
+                &event_ticket_pass.event_name,
+                fields::ContentOptions {
+                    label: "Event".to_string().into(),
+                    ..Default::default()
+                },
+            ));
+
+            if let Some(attendee_name) = &event_ticket_pass.attendee_name {
+                f = f.add_secondary_field(fields::Content::new(
+                    "attendee",
+                    attendee_name,
+                    fields::ContentOptions {
+                        label: "Teilnehmer".to_string().into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if let Some(seat) = &event_ticket_pass.seat {
+                f = f.add_secondary_field(fields::Content::new(
+                    "seat",
+                    seat,
+                    fields::ContentOptions {
+                        label: "Platz".to_string().into(),
+                        text_alignment: Some(TextAlignment::Right),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if let Some(venue) = &event_ticket_pass.venue {
+                f = f.add_auxiliary_field(fields::Content::new(
+                    "venue",
+                    venue,
+                    fields::ContentOptions {
+                        label: "Ort".to_string().into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            if let Some(checked_in_at) = event_ticket_pass.checked_in_at {
+                f = f.add_back_field(fields::Content::new(
+                    "checked_in_at",
+                    &checked_in_at.to_rfc3339(),
+                    fields::ContentOptions {
+                        label: "Eingelöst".to_string().into(),
+                        time_style: DateStyle::Short.into(),
+                        date_style: DateStyle::Medium.into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            identity_back_fields(
+                f,
+                &member_code,
+                &serial_number,
+                customer_view_url.as_deref(),
+            )
+        })
+        .add_barcode(barcode.into())
+        .web_service(WebService {
+            web_service_url: self.web_service_url.clone(),
+            authentication_token,
+        });
+
+        if let Some(ends_at) = event_ticket_pass.ends_at {
+            builder = builder.expiration_date(ends_at);
+        }
+
+        self.new_package(builder.build(), i_sign_config)
+    }
+
+    pub fn new_gift_card_pass(
+        &self,
+        pass_type_identifier: &str,
+        i_sign_config: &ISignConfig,
+        identity: PassIdentity,
+        gift_card_pass: GiftCardPass,
+    ) -> Result<Package> {
+        let PassIdentity {
+            serial_number,
+            authentication_token,
+            member_code,
+            barcode,
+            customer_view_url,
+        } = identity;
+
+        let pass = PassBuilder::new(PassConfig {
+            organization_name: "Boulder Bubbletea".into(),
+            description: "Boulder Bubbletea Geschenkkarte".into(),
+            pass_type_identifier: pass_type_identifier.to_string(),
+            team_identifier: self.team_identifier.clone(),
+            serial_number: serial_number.clone(),
+        })
+        .appearance(VisualAppearance {
+            label_color: Color::white(),
+            foreground_color: Color::white(),
+            background_color: Color::new(255, 145, 160),
+        })
+        // Gift cards are meant to be passed on.
+        .set_sharing_prohibited(false)
+        // The passes crate has no store card type, the generic type has the same layout.
+        .fields({
+            let mut f = fields::Type::Generic {
+                pass_fields: fields::Fields::default(),
+            }
+            .add_header_field(fields::Content::new(
+                "name",
+                "Boulder Bubbletea",
+                fields::ContentOptions {
+                    label: "Store".to_string().into(),
+                    ..Default::default()
+                },
+            ))
+            .add_primary_field(fields::Content::new(
+                "balance",
+                &format_money(gift_card_pass.balance, &gift_card_pass.currency),
+                fields::ContentOptions {
+                    label: "Guthaben".to_string().into(),
+                    change_message: "Neues Guthaben: %@".to_string().into(),
+                    ..Default::default()
+                },
+            ));
+
+            if let Some(pass_holder_name) = &gift_card_pass.pass_holder_name {
+                f = f.add_secondary_field(fields::Content::new(
+                    "pass_holder",
+                    pass_holder_name,
+                    fields::ContentOptions {
+                        label: "Diese Karte gehört".to_string().into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            f = identity_back_fields(
+                f,
+                &member_code,
+                &serial_number,
+                customer_view_url.as_deref(),
+            );
+
+            if let Some(last_use) = gift_card_pass.last_use {
+                f = f.add_back_field(fields::Content::new(
+                    "last_use",
+                    &last_use.to_rfc3339(),
+                    fields::ContentOptions {
+                        label: "Letzte Nutzung".to_string().into(),
+                        time_style: DateStyle::Medium.into(),
+                        date_style: DateStyle::Medium.into(),
+                        ..Default::default()
+                    },
+                ));
+            }
+            f
+        })
+        .add_barcode(barcode.into())
         .web_service(WebService {
             web_service_url: self.web_service_url.clone(),
             authentication_token,
         })
         .build();
 
+        self.new_package(pass, i_sign_config)
+    }
+
+    /// Creates the package of the pass with the resources all passes share.
+    fn new_package(&self, pass: Pass, i_sign_config: &ISignConfig) -> Result<Package> {
         let mut package = Package::new(pass);
 
         let image_path = Path::new(&self.icon_path);
@@ -165,17 +671,7 @@ impl PassMaker {
             .add_resource(resource::Type::Icon(resource::Version::Size2X), file)
             .unwrap();
 
-        package
-            .add_resource(
-                resource::Type::Strip(resource::Version::Size2X),
-                Cursor::new(self.image_maker.generate_points_image(
-                    loyality_pass.total_points.try_into().unwrap(),
-                    loyality_pass.current_points.try_into().unwrap(),
-                )?),
-            )
-            .unwrap();
-
-        package.add_certificates(self.i_sign_config.new_sign_config()?);
+        package.add_certificates(i_sign_config.new_sign_config()?);
 
         Ok(package)
     }
@@ -185,10 +681,13 @@ impl PassMaker {
 pub struct ISignConfig {
     pub sign_cert: Vec<u8>,
     pub sign_key: Vec<u8>,
+    /// The Apple WWDR intermediate certificate the signing certificate was issued by, the bundled
+    /// G4 certificate is used if not set.
+    pub wwdr_cert: Option<Vec<u8>>,
 }
 
 impl ISignConfig {
-    pub fn new(cert: &str, key: &str, passphrase: &str) -> Result<Self> {
+    pub fn new(cert: &str, key: &str, passphrase: &str, wwdr_cert: Option<&str>) -> Result<Self> {
         let sign_cert_path = Path::new(cert);
         let mut file_sign_cert = File::open(sign_cert_path)?;
         let mut sign_cert_data = Vec::new();
@@ -203,25 +702,99 @@ impl ISignConfig {
         let rsa = Rsa::private_key_from_pem_passphrase(&sign_cert_key_data, passphrase.as_bytes())?;
         let decrypted_key_pem = rsa.private_key_to_pem()?;
 
+        let wwdr_cert = wwdr_cert.map(std::fs::read).transpose()?;
+
         Ok(Self {
             sign_cert: sign_cert_data,
             sign_key: decrypted_key_pem,
+            wwdr_cert,
         })
     }
 
     fn new_sign_config(&self) -> Result<SignConfig> {
         Ok(SignConfig::new(
-            sign::WWDR::G4,
+            match &self.wwdr_cert {
+                Some(wwdr_cert) => sign::WWDR::Custom(wwdr_cert),
+                None => sign::WWDR::G4,
+            },
             &self.sign_cert,
             &self.sign_key,
         )?)
     }
 }
 
+/// The back fields with which the customer and the staff identify the pass.
+fn identity_back_fields(
+    mut f: fields::Type,
+    member_code: &str,
+    serial_number: &str,
+    customer_view_url: Option<&str>,
+) -> fields::Type {
+    if let Some(customer_view_url) = customer_view_url {
+        f = f.add_back_field(fields::Content::new(
+            "customer-view",
+            customer_view_url,
+            fields::ContentOptions {
+                label: String::from("Online").into(),
+                attributed_value: format!("<a href='{customer_view_url}'>Pass online ansehen</a>")
+                    .into(),
+                ..Default::default()
+            },
+        ));
+    }
+
+    f.add_back_field(fields::Content::new(
+        "member-code",
+        &member_code::display(member_code),
+        fields::ContentOptions {
+            label: String::from("Mitgliedsnummer").into(),
+            ..Default::default()
+        },
+    ))
+    .add_back_field(fields::Content::new(
+        "serial-number",
+        serial_number,
+        fields::ContentOptions {
+            label: String::from("Serial Number").into(),
+            ..Default::default()
+        },
+    ))
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Option<Color> {
+    Color::new(r, g, b)
+}
+
+/// Formats an amount in minor currency units the way it is shown on the passes, e.g. `12,50€`.
+pub fn format_money(amount: i64, currency: &str) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let value = format!(
+        "{sign}{},{:02}",
+        amount.unsigned_abs() / 100,
+        amount.unsigned_abs() % 100
+    );
+
+    match currency {
+        "EUR" => format!("{value}€"),
+        "USD" => format!("{value}$"),
+        "GBP" => format!("{value}£"),
+        other => format!("{value} {other}"),
+    }
+}
+
+/// The `.pkpass` file of the package, e.g. to attach it to an email.
+pub fn package_bytes(package: &mut Package) -> Result<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+
+    write_package(package, &mut buffer)?;
+
+    Ok(buffer.into_inner())
+}
+
 pub fn body_from_package(package: &mut Package) -> Result<Body> {
     let mut buffer = Cursor::new(Vec::new());
 
-    package.write(&mut buffer).map_err(|_| Error::Unknown)?;
+    write_package(package, &mut buffer)?;
 
     let _ = buffer.seek(SeekFrom::Start(0))?;
 
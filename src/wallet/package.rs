@@ -0,0 +1,250 @@
+use std::{
+    collections::BTreeMap,
+    io::{Seek, Write},
+};
+
+use openssl::{
+    pkcs7::{Pkcs7, Pkcs7Flags},
+    stack::Stack,
+};
+use passes::{manifest::Manifest, Package};
+use serde_json::Value;
+use zip::{result::ZipError, write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::Result;
+
+/// English translations of the fixed texts of the passes, keyed by the German text in
+/// `pass.json`.
+const ENGLISH: &[(&str, &str)] = &[
+    (
+        "Bonus eingelöst! Insgesamt schon %@ mal.",
+        "Bonus redeemed! %@ times so far.",
+    ),
+    (
+        "Neuer Stempel! Du hast jetzt %@.",
+        "New stamp! You now have %@.",
+    ),
+    ("Neuer Status: %@", "New status: %@"),
+    ("Neues Guthaben: %@", "New balance: %@"),
+    ("Bereits eingelöst", "Already redeemed"),
+    ("Gesammelt, Bonus bereit", "Collected, bonus ready"),
+    ("Guthaben", "Balance"),
+    ("Basis", "Base"),
+];
+
+/// The English text for a German text of a pass, including the texts with the points of the
+/// loyality pass.
+fn english(text: &str) -> Option<String> {
+    if let Some((_, english)) = ENGLISH.iter().find(|(german, _)| *german == text) {
+        return Some(english.to_string());
+    }
+
+    if let Some(remaining) = text
+        .strip_prefix("Gesammelt, noch ")
+        .and_then(|t| t.strip_suffix(" bis zum Bonus"))
+    {
+        return Some(format!("Collected, {remaining} more until the bonus"));
+    }
+
+    let (current, total) = text.strip_suffix(" Stempeln")?.split_once(" von ")?;
+
+    Some(format!("{current} of {total} stamps"))
+}
+
+/// Collects the labels, values and change messages of all fields of `pass.json`.
+fn field_texts<'a>(value: &'a Value, texts: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("label" | "value" | "changeMessage", Value::String(text)) => texts.push(text),
+                    _ => field_texts(value, texts),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| field_texts(value, texts)),
+        _ => {}
+    }
+}
+
+/// The `pass.strings` files of the languages, keyed by the German texts of `pass.json`. Wallet
+/// shows the texts of `pass.json` for languages without a file.
+fn localizations(pass_json: &str) -> Result<Vec<(&'static str, String)>> {
+    let pass: Value = serde_json::from_str(pass_json).map_err(std::io::Error::from)?;
+
+    let mut texts = Vec::new();
+    field_texts(&pass, &mut texts);
+
+    let translations: BTreeMap<&str, String> = texts
+        .into_iter()
+        .filter_map(|text| english(text).map(|english| (text, english)))
+        .collect();
+
+    if translations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // German is listed as well, otherwise Wallet would pick English on German devices.
+    Ok(vec![
+        (
+            "de",
+            strings_file(translations.keys().map(|german| (*german, *german))),
+        ),
+        (
+            "en",
+            strings_file(
+                translations
+                    .iter()
+                    .map(|(german, english)| (*german, english.as_str())),
+            ),
+        ),
+    ])
+}
+
+fn strings_file<'a>(entries: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let escape = |text: &str| {
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    };
+
+    entries
+        .map(|(key, text)| format!("\"{}\" = \"{}\";\n", escape(key), escape(text)))
+        .collect()
+}
+
+/// Writes the `.pkpass` file of the package like [`Package::write`], with the `.lproj` string
+/// files that localize the texts of the pass.
+pub(super) fn write_package<W: Write + Seek>(package: &Package, writer: W) -> Result<()> {
+    let pass_json = package.pass.make_json().map_err(std::io::Error::from)?;
+
+    let mut files = vec![("pass.json".to_string(), pass_json.clone().into_bytes())];
+    files.extend(
+        package
+            .resources
+            .iter()
+            .map(|resource| (resource.filename(), resource.as_bytes().to_vec())),
+    );
+    files.extend(
+        localizations(&pass_json)?
+            .into_iter()
+            .map(|(language, strings)| (format!("{language}.lproj/pass.strings"), strings.into())),
+    );
+
+    let mut zip = ZipWriter::new(writer);
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut manifest = Manifest::new();
+
+    for (name, data) in &files {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(data)?;
+        manifest.add_item(name, data);
+    }
+
+    let manifest_json = manifest.make_json().map_err(std::io::Error::from)?;
+    zip.start_file("manifest.json", options)
+        .map_err(zip_error)?;
+    zip.write_all(manifest_json.as_bytes())?;
+
+    if let Some(sign_config) = &package.sign_config {
+        let mut certs = Stack::new()?;
+        certs.push(sign_config.cert.clone())?;
+
+        let signature = Pkcs7::sign(
+            &sign_config.sign_cert,
+            &sign_config.sign_key,
+            &certs,
+            manifest_json.as_bytes(),
+            Pkcs7Flags::DETACHED,
+        )?;
+
+        zip.start_file("signature", options).map_err(zip_error)?;
+        zip.write_all(&signature.to_der()?)?;
+    }
+
+    zip.finish().map_err(zip_error)?;
+
+    Ok(())
+}
+
+fn zip_error(err: ZipError) -> std::io::Error {
+    err.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use passes::{fields, Package, PassBuilder, PassConfig};
+
+    use super::{english, write_package};
+
+    #[test]
+    fn translates_texts_with_points() {
+        assert_eq!(
+            english("Neues Guthaben: %@").as_deref(),
+            Some("New balance: %@")
+        );
+        assert_eq!(
+            english("3 von 10 Stempeln").as_deref(),
+            Some("3 of 10 stamps")
+        );
+        assert_eq!(
+            english("Gesammelt, noch 7 bis zum Bonus").as_deref(),
+            Some("Collected, 7 more until the bonus")
+        );
+        assert_eq!(english("Max Mustermann"), None);
+    }
+
+    #[test]
+    fn writes_localized_strings_into_the_manifest() {
+        let pass = PassBuilder::new(PassConfig {
+            organization_name: "Boulder Bubbletea".into(),
+            description: "Boulder Bubbletea Geschenkkarte".into(),
+            pass_type_identifier: "pass.com.example".into(),
+            team_identifier: "AA00AA0A0A".into(),
+            serial_number: "serial".into(),
+        })
+        .fields(
+            fields::Type::Generic {
+                pass_fields: fields::Fields::default(),
+            }
+            .add_primary_field(fields::Content::new(
+                "balance",
+                "12,50€",
+                fields::ContentOptions {
+                    label: "Guthaben".to_string().into(),
+                    change_message: "Neues Guthaben: %@".to_string().into(),
+                    ..Default::default()
+                },
+            )),
+        )
+        .build();
+
+        let mut buffer = Cursor::new(Vec::new());
+        write_package(&Package::new(pass), &mut buffer).unwrap();
+
+        let mut zip = zip::ZipArchive::new(buffer).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+
+        assert_eq!(
+            read("en.lproj/pass.strings"),
+            "\"Guthaben\" = \"Balance\";\n\"Neues Guthaben: %@\" = \"New balance: %@\";\n"
+        );
+        assert_eq!(
+            read("de.lproj/pass.strings"),
+            "\"Guthaben\" = \"Guthaben\";\n\"Neues Guthaben: %@\" = \"Neues Guthaben: %@\";\n"
+        );
+
+        let manifest = read("manifest.json");
+        assert!(manifest.contains("en.lproj/pass.strings"));
+        assert!(manifest.contains("de.lproj/pass.strings"));
+    }
+}